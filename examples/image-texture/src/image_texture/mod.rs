@@ -1,8 +1,9 @@
 use image::GenericImageView;
+use std::any::Any;
 use wgpu_egui_tokio::{
-    Page, Render, WgpuState, egui,
+    Command, Navigator, Page, PagePolicy, Params, Render, WgpuState, egui,
     wgpu::{self, Color, CommandEncoder, TextureView, include_wgsl, util::DeviceExt},
-    winit::event::WindowEvent,
+    winit::dpi::PhysicalSize,
 };
 
 // 计算图像在屏幕上的缩放比例，返回一个包含宽度和高度缩放比例的数组
@@ -55,7 +56,9 @@ pub struct StudyImageTexture {
     pub address_mode_v: wgpu::AddressMode, // 纹理 V 轴寻址模式
     pub loading: bool,                     // 是否正在加载图像
     pub image_url: String,                 // 图像 URL
+    pub error: Option<String>,             // 最近一次加载失败的原因
     sender: tokio::sync::mpsc::Sender<Message>,
+    navigator: Navigator,
     pub pipeline: wgpu::RenderPipeline, // 渲染管线（包含着色器、状态配置等）
     pub bind_group: wgpu::BindGroup,
     pub texture: wgpu::Texture,
@@ -64,11 +67,26 @@ pub struct StudyImageTexture {
     pub image_dimensions: [f32; 2],
 }
 
+// 页面卸载时保留的控件状态
+struct Snapshot {
+    mag_filter: wgpu::FilterMode,
+    address_mode_u: wgpu::AddressMode,
+    address_mode_v: wgpu::AddressMode,
+    image_url: String,
+}
+
 impl Page for StudyImageTexture {
     type Message = Message;
+    const ID: &'static str = "image-texture";
+    const TITLE: &'static str = "Image Texture";
+    const CATEGORY: Option<&'static str> = Some("Texture");
+    const ORDER: i32 = 1;
+    // 网络图片纹理可能很大，离开页面时释放
+    const POLICY: PagePolicy = PagePolicy::UnloadOnLeave;
     fn new(
         state: &wgpu_egui_tokio::WgpuState,
         sender: tokio::sync::mpsc::Sender<Self::Message>,
+        _cancel: wgpu_egui_tokio::CancellationToken,
     ) -> Self
     where
         Self: Sized,
@@ -105,7 +123,7 @@ impl Page for StudyImageTexture {
             }),
             primitive: Default::default(), // 使用默认图元配置（三角形列表）
             depth_stencil: None,           // 禁用深度/模板测试
-            multisample: Default::default(), // 多重采样配置
+            multisample: state.multisample_state(), // 多重采样配置（与 WgpuState 一致）
             multiview: None,
             cache: None,
         });
@@ -192,7 +210,9 @@ impl Page for StudyImageTexture {
             address_mode_u: wgpu::AddressMode::ClampToEdge, // 默认 U 轴边缘拉伸
             address_mode_v: wgpu::AddressMode::ClampToEdge, // 默认 V 轴边缘拉伸
             image_url: String::new(),              // 默认空字符串
+            error: None,
             sender,
+            navigator: state.navigator(),
             pipeline,
             bind_group,
             texture,
@@ -212,7 +232,7 @@ impl Page for StudyImageTexture {
             config,
             ..
         }: &wgpu_egui_tokio::WgpuState,
-    ) {
+    ) -> Command<Self::Message> {
         match message {
             Message::Sampler => {
                 // 根据控件设置创建采样器
@@ -244,15 +264,15 @@ impl Page for StudyImageTexture {
             }
             Message::Load => {
                 self.loading = true;
-                tokio::spawn({
-                    let url = self.image_url.clone();
-                    let sender = self.sender.clone();
+                self.error = None;
+                let url = self.image_url.clone();
+                return Command::try_perform(
                     async move {
-                        let bytes = reqwest::get(url).await.unwrap().bytes().await.unwrap();
-                        let image = image::load_from_memory(&bytes).unwrap();
-                        sender.send(Message::LoadedImage(image)).await.unwrap();
-                    }
-                });
+                        let bytes = reqwest::get(url).await?.error_for_status()?.bytes().await?;
+                        anyhow::Ok(image::load_from_memory(&bytes)?)
+                    },
+                    Message::LoadedImage,
+                );
             }
             Message::LoadedImage(image) => {
                 self.loading = false;
@@ -318,6 +338,57 @@ impl Page for StudyImageTexture {
                 self.bind_group = bind_group;
             }
         }
+        Command::none()
+    }
+
+    // 支持 `image-texture?url=...` 直接加载图片
+    fn on_enter(&mut self, params: &Params, _state: &WgpuState) {
+        if let Some(url) = params.raw("url")
+            && url != self.image_url
+        {
+            self.image_url = url.to_string();
+            let _ = self.sender.try_send(Message::Load);
+        }
+    }
+
+    fn snapshot(&self) -> Option<Box<dyn Any + Send>> {
+        Some(Box::new(Snapshot {
+            mag_filter: self.mag_filter,
+            address_mode_u: self.address_mode_u,
+            address_mode_v: self.address_mode_v,
+            image_url: self.image_url.clone(),
+        }))
+    }
+
+    // 恢复控件状态后重新创建采样器，并重新加载之前的图片
+    fn restore(&mut self, snapshot: Box<dyn Any + Send>, _state: &WgpuState) {
+        let Ok(snapshot) = snapshot.downcast::<Snapshot>() else {
+            return;
+        };
+        self.mag_filter = snapshot.mag_filter;
+        self.address_mode_u = snapshot.address_mode_u;
+        self.address_mode_v = snapshot.address_mode_v;
+        self.image_url = snapshot.image_url;
+        let _ = self.sender.try_send(Message::Sampler);
+        if !self.image_url.is_empty() {
+            let _ = self.sender.try_send(Message::Load);
+        }
+    }
+
+    // 不可见时的尺寸变化会在下次进入页面时补发，缩放比例不会过期
+    fn on_resize(&mut self, size: PhysicalSize<u32>, state: &WgpuState) {
+        let scale = calc_scale(
+            self.image_dimensions,
+            [size.width as f32, size.height as f32],
+        );
+        state
+            .queue
+            .write_buffer(&self.scale_buffer, 0, bytemuck::cast_slice(&scale));
+    }
+
+    fn on_error(&mut self, error: anyhow::Error, _state: &WgpuState) {
+        self.loading = false;
+        self.error = Some(format!("Failed to load image: {:#}", error));
     }
 }
 
@@ -436,25 +507,32 @@ impl Render for StudyImageTexture {
                     ui.spinner();
                 });
             }
+
+            if let Some(error) = &self.error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+
+            if self.navigator.can_go_back() && ui.button("Back").clicked() {
+                self.navigator.back();
+            }
         });
     }
 
     fn render(
         &self,
-        _state: &WgpuState,
+        state: &WgpuState,
         view: &TextureView,
         encoder: &mut CommandEncoder,
     ) -> anyhow::Result<()> {
         let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Render Pass"),
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &view,
-                ops: wgpu::Operations {
+            color_attachments: &[Some(state.color_attachment(
+                view,
+                wgpu::Operations {
                     load: wgpu::LoadOp::Clear(Color::BLACK), // 用黑色清除背景
                     store: wgpu::StoreOp::Store,             // 存储渲染结果
                 },
-                resolve_target: None,
-            })],
+            ))],
             depth_stencil_attachment: None,
             timestamp_writes: None,
             occlusion_query_set: None,
@@ -471,23 +549,4 @@ impl Render for StudyImageTexture {
 
         Ok(())
     }
-
-    fn handle_event(
-        &mut self,
-        event: wgpu_egui_tokio::winit::event::WindowEvent,
-        state: &WgpuState,
-    ) {
-        match event {
-            WindowEvent::Resized(_) => {
-                let scale = calc_scale(
-                    self.image_dimensions,
-                    [state.config.width as f32, state.config.height as f32],
-                );
-                state
-                    .queue
-                    .write_buffer(&self.scale_buffer, 0, bytemuck::cast_slice(&scale));
-            }
-            _ => {}
-        }
-    }
 }
@@ -1,13 +1,20 @@
 use image_texture::{Message, Simple, StudyImageTexture};
-use wgpu_egui_tokio::{App, winit};
+use wgpu_egui_tokio::{App, NavigationUi, Route};
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
+// App::run 自己创建 tokio 运行时，main 不需要是异步的
+fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt::init();
-    let event_loop = winit::event_loop::EventLoop::new()?;
-    let mut app = App::new();
-    app.register::<StudyImageTexture, Message>();
-    app.register::<Simple, ()>();
-    event_loop.run_app(&mut app)?;
+    let mut app = App::builder()
+        .title("Image Texture")
+        .navigation(NavigationUi::SidePanel)
+        .build();
+    app.register::<StudyImageTexture, Message>()?;
+    app.register::<Simple, ()>()?;
+    // 例如 `cargo run -- --page "image-texture?url=https://..."`
+    match Route::from_args(std::env::args())? {
+        Some(route) => app.start_at(route),
+        None => app.start_on::<Simple>(),
+    }
+    app.run()?;
     Ok(())
 }
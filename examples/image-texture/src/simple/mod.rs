@@ -1,73 +1,118 @@
 use wgpu_egui_tokio::{
-    Page, Render, WgpuState,
-    wgpu::{self, include_wgsl},
+    FrameContext, Navigator, Page, PagePolicy, Render, ShaderAsset, ShaderPipeline, egui, wgpu,
 };
 
+// 默认直接从源码目录加载，修改着色器后保存即可看到效果，不需要重新编译；
+// 在其他位置运行时可以通过 TRIANGLE_SHADER 环境变量指定文件
+const SHADER_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/simple/trangle.wgsl");
+
+fn shader_path() -> String {
+    std::env::var("TRIANGLE_SHADER").unwrap_or_else(|_| SHADER_PATH.to_string())
+}
+
 pub struct Simple {
-    pub pipeline: wgpu::RenderPipeline,
+    // 着色器文件无法读取时为 None，页面只显示错误
+    pub pipeline: Option<ShaderPipeline<wgpu::RenderPipeline>>,
+    load_error: Option<String>,
+    navigator: Navigator,
 }
 
 impl Page for Simple {
     type Message = ();
+    const ID: &'static str = "triangle";
+    const TITLE: &'static str = "Triangle";
+    const CATEGORY: Option<&'static str> = Some("Basics");
+    const POLICY: PagePolicy = PagePolicy::Lazy;
     fn new(
-        WgpuState { device, config, .. }: &wgpu_egui_tokio::WgpuState,
+        state: &wgpu_egui_tokio::WgpuState,
         _sender: tokio::sync::mpsc::Sender<Self::Message>,
+        _cancel: wgpu_egui_tokio::CancellationToken,
     ) -> Self
     where
         Self: Sized,
     {
-        let shader = device.create_shader_module(include_wgsl!("trangle.wgsl"));
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("triangle"),
-            layout: None,
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs"),
-                compilation_options: Default::default(),
-                buffers: &[],
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs"),
-                compilation_options: Default::default(),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: Default::default(),
-            multisample: Default::default(),
-            depth_stencil: None,
-            multiview: None,
-            cache: None,
+        let shader = match ShaderAsset::load(state, shader_path()) {
+            Ok(shader) => shader,
+            Err(err) => {
+                return Self {
+                    pipeline: None,
+                    load_error: Some(format!("{:#}", err)),
+                    navigator: state.navigator(),
+                };
+            }
+        };
+        let pipeline = ShaderPipeline::new(state, &shader, |state, shader| {
+            state
+                .device
+                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("triangle"),
+                    layout: None,
+                    vertex: wgpu::VertexState {
+                        module: shader,
+                        entry_point: Some("vs"),
+                        compilation_options: Default::default(),
+                        buffers: &[],
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: shader,
+                        entry_point: Some("fs"),
+                        compilation_options: Default::default(),
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format: state.config.format,
+                            blend: Some(wgpu::BlendState::REPLACE),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                    }),
+                    primitive: Default::default(),
+                    multisample: state.multisample_state(),
+                    depth_stencil: None,
+                    multiview: None,
+                    cache: None,
+                })
         });
-        Self { pipeline }
+        Self {
+            pipeline: Some(pipeline),
+            load_error: None,
+            navigator: state.navigator(),
+        }
     }
 }
 
 impl Render for Simple {
-    fn render(
-        &self,
-        _state: &WgpuState,
-        view: &wgpu::TextureView,
-        encoder: &mut wgpu::CommandEncoder,
-    ) -> anyhow::Result<()> {
-        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("Render Pass"),
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view,
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Load,
-                    store: wgpu::StoreOp::Store,
-                },
-            })],
-            depth_stencil_attachment: None,
-            timestamp_writes: None,
-            occlusion_query_set: None,
+    fn ui_draw(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Tour").show(ctx, |ui| {
+            if let Some(error) = &self.load_error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+            if ui.button("Next: Image Texture").clicked() {
+                self.navigator.push("image-texture");
+            }
+        });
+    }
+
+    fn render_frame(&self, frame: &mut FrameContext) -> anyhow::Result<()> {
+        // 着色器还没有成功编译过，错误显示在 "Shader Errors" 窗口中
+        let Some(pipeline) = self
+            .pipeline
+            .as_ref()
+            .and_then(|pipeline| pipeline.get(frame.state))
+        else {
+            return Ok(());
+        };
+        let color_attachment = frame.color_attachment(wgpu::Operations {
+            load: wgpu::LoadOp::Load,
+            store: wgpu::StoreOp::Store,
         });
-        pass.set_pipeline(&self.pipeline);
+        let mut pass = frame
+            .encoder
+            .begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(color_attachment)],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+        pass.set_pipeline(&pipeline);
         pass.draw(0..3, 0..1);
         Ok(())
     }
@@ -0,0 +1,9 @@
+use image_texture::Simple;
+use wgpu_egui_tokio::testing::PageHarness;
+
+// 参考图像由软件 adapter 渲染，修改页面后用 UPDATE_SNAPSHOTS=1 cargo test 更新
+#[test]
+fn triangle_matches_snapshot() -> anyhow::Result<()> {
+    let mut harness = PageHarness::<Simple>::new(320, 240)?;
+    harness.assert_snapshot("triangle", 3, 2)
+}
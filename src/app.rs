@@ -1,24 +1,86 @@
 use crate::{
     Render,
+    capture::Readback,
+    error::GpuError,
+    event::{AppEvent, RepaintHandle, WindowRequest},
+    frame::FrameContext,
+    navigation::{NavigationShortcuts, NavigationUi},
     page::{Page, Pages},
-    state::WgpuState,
+    route::Route,
+    shader,
+    state::{GpuContext, GpuOptions, WgpuState},
 };
 use egui_wgpu::ScreenDescriptor;
-use std::sync::{Arc, Mutex};
-use wgpu::{CommandEncoderDescriptor, TextureViewDescriptor};
-use winit::{application::ApplicationHandler, event::WindowEvent, window::WindowAttributes};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    runtime::{Handle, Runtime, RuntimeFlavor},
+    sync::oneshot,
+};
+use tokio_util::sync::CancellationToken;
+use wgpu::{
+    Backends, CommandEncoderDescriptor, Features, Limits, Maintain, PowerPreference, PresentMode,
+    TextureFormat,
+};
+use winit::{
+    application::ApplicationHandler,
+    dpi::PhysicalSize,
+    event::{ElementState, KeyEvent, WindowEvent},
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoop, EventLoopProxy},
+    keyboard::{KeyCode, PhysicalKey},
+    window::{Fullscreen, Icon, WindowAttributes, WindowId},
+};
 
-pub struct App {
-    pub state: Arc<Mutex<Option<WgpuState>>>,
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RedrawMode {
+    // 每帧结束后立即请求下一帧
+    #[default]
+    Continuous,
+    // 仅在输入、egui 请求重绘、页面消息或 request_repaint 时重绘，适合长时间空闲的工具类应用；
+    // 页面可以通过 Render::continuous_redraw 单独要求持续重绘
+    Reactive,
+}
+
+// 每个窗口拥有独立的表面、EguiRenderer 和页面，Device/Queue 在窗口之间共享
+pub struct WindowContext {
+    pub state: WgpuState,
     pub pages: Pages,
+    pub is_main: bool,
+}
+
+type FatalErrorHandler = Box<dyn FnMut(&GpuError)>;
+
+pub struct App {
+    pub windows: HashMap<WindowId, WindowContext>,
+    // 尚未创建的窗口，第一个为主窗口
+    pub pending: Vec<(WindowAttributes, Pages)>,
+    pub options: GpuOptions,
+    pub fixed_timestep: Option<Duration>,
+    pub redraw_mode: RedrawMode,
+    pub proxy: Option<EventLoopProxy<AppEvent>>,
+    // 关闭窗口或退出时等待页面未完成任务的最长时间
+    pub shutdown_timeout: Duration,
+    on_fatal_error: FatalErrorHandler,
+    // 所有窗口取消令牌的根
+    cancellation: CancellationToken,
+    // run 之前为 AppBuilder 指定的运行时，run 之后为该运行时的句柄
+    runtime: Option<RuntimeConfig>,
+    handle: Option<Handle>,
+    // 主窗口创建失败的原因，事件循环结束后由 run 返回
+    startup_error: Option<anyhow::Error>,
+}
+
+enum RuntimeConfig {
+    Runtime(Runtime),
+    Builder(tokio::runtime::Builder),
 }
 
 impl Default for App {
     fn default() -> Self {
-        Self {
-            state: Arc::new(Mutex::new(None)),
-            pages: Pages::new(),
-        }
+        Self::builder().build()
     }
 }
 
@@ -27,117 +89,748 @@ impl App {
         Self::default()
     }
 
-    pub fn register<T, M>(&mut self)
+    pub fn builder() -> AppBuilder {
+        AppBuilder::default()
+    }
+
+    // 在主窗口中注册页面，ID 重复时返回错误
+    pub fn register<T, M>(&mut self) -> anyhow::Result<()>
     where
-        T: Page<Message = M> + Send + Sync + 'static,
-        M: Send + Sync + 'static,
+        T: Page<Message = M> + 'static,
+        M: Send + 'static,
     {
-        self.pages.register::<T, M>();
+        self.pending[0].1.register::<T, M>()
+    }
+
+    // 主窗口的初始页面
+    pub fn start_on<T: Page>(&mut self) {
+        self.pending[0].1.start_on::<T>();
+    }
+
+    // 带参数的初始页面，可以配合 Route::from_args 从命令行启动到指定页面
+    pub fn start_at(&mut self, route: Route) {
+        self.pending[0].1.start_at(route);
+    }
+
+    // 添加一个额外的窗口，返回该窗口的页面注册表
+    pub fn add_window(&mut self, attributes: WindowAttributes) -> &mut Pages {
+        self.pending.push((attributes, Pages::new()));
+        &mut self.pending.last_mut().unwrap().1
+    }
+
+    // 在主线程上运行事件循环，页面命令、订阅和后台任务在 tokio 运行时上执行。
+    // 没有通过 AppBuilder 指定运行时时：已经在多线程运行时中（例如 #[tokio::main]）则直接使用它，否则创建多线程运行时
+    pub fn run(mut self) -> anyhow::Result<()> {
+        let runtime = match self.runtime.take() {
+            Some(RuntimeConfig::Runtime(runtime)) => Some(runtime),
+            Some(RuntimeConfig::Builder(mut builder)) => Some(builder.enable_all().build()?),
+            // 事件循环会阻塞当前线程，current_thread 运行时将无法再执行任何任务，所以直接返回错误
+            None if Handle::try_current().is_ok() => {
+                if Handle::current().runtime_flavor() == RuntimeFlavor::CurrentThread {
+                    anyhow::bail!(
+                        "App::run cannot block a current_thread tokio runtime, \
+                         use a multi-thread runtime or AppBuilder::runtime_builder instead"
+                    );
+                }
+                None
+            }
+            None => Some(
+                tokio::runtime::Builder::new_multi_thread()
+                    .enable_all()
+                    .build()?,
+            ),
+        };
+        let handle = match &runtime {
+            Some(runtime) => runtime.handle().clone(),
+            None => Handle::current(),
+        };
+        self.handle = Some(handle.clone());
+
+        let result = std::thread::scope(|scope| {
+            // current_thread 运行时没有工作线程，需要在单独的线程上驱动它
+            let (stop, stopped) = oneshot::channel::<()>();
+            if handle.runtime_flavor() == RuntimeFlavor::CurrentThread
+                && let Some(runtime) = &runtime
+            {
+                scope.spawn(move || {
+                    runtime.block_on(async {
+                        let _ = stopped.await;
+                    })
+                });
+            }
+            // 页面可以直接调用 tokio::spawn
+            let _guard = handle.enter();
+            let result = self.run_event_loop();
+            drop(stop);
+            result
+        });
+
+        if let Some(runtime) = runtime {
+            shutdown_runtime(runtime, self.shutdown_timeout);
+        }
+        result
+    }
+
+    // 创建带用户事件的事件循环并运行，页面消息和后台任务可通过 EventLoopProxy 唤醒窗口
+    fn run_event_loop(&mut self) -> anyhow::Result<()> {
+        let event_loop = EventLoop::<AppEvent>::with_user_event().build()?;
+        self.proxy = Some(event_loop.create_proxy());
+        event_loop.run_app(self)?;
+        match self.startup_error.take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    fn create_windows(&mut self, event_loop: &ActiveEventLoop) -> anyhow::Result<()> {
+        // 已有窗口时复用其 Device/Queue
+        let mut shared = self
+            .windows
+            .values()
+            .next()
+            .map(|context| context.state.gpu());
+
+        // 页面命令在 App::run 的运行时上执行
+        let handle = self
+            .handle
+            .clone()
+            .ok_or(anyhow::anyhow!("App has no tokio runtime"))?;
+
+        let is_first = self.windows.is_empty();
+        let pending = std::mem::take(&mut self.pending);
+        for (index, (attributes, pages)) in pending.into_iter().enumerate() {
+            let is_main = is_first && index == 0;
+            if let Err(err) =
+                self.create_window(event_loop, attributes, pages, &handle, &mut shared, is_main)
+            {
+                // 主窗口是必需的，其他窗口创建失败时只记录错误，继续创建剩下的窗口
+                if is_main {
+                    return Err(err);
+                }
+                tracing::error!("Failed to create window: {:#}", err);
+            }
+        }
+        Ok(())
+    }
+
+    fn create_window(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        attributes: WindowAttributes,
+        mut pages: Pages,
+        handle: &Handle,
+        shared: &mut Option<GpuContext>,
+        is_main: bool,
+    ) -> anyhow::Result<()> {
+        let window = Arc::new(event_loop.create_window(attributes)?);
+        let mut state = match shared {
+            Some(gpu) => WgpuState::with_gpu(window.clone(), gpu.clone(), self.options.clone())?,
+            None => pollster::block_on(WgpuState::with_options(
+                window.clone(),
+                self.options.clone(),
+            ))?,
+        };
+        state.set_fixed_timestep(self.fixed_timestep);
+        state.cancellation = self.cancellation.child_token();
+        if let Some(proxy) = &self.proxy {
+            state.set_repaint_handle(RepaintHandle::new(proxy.clone(), window.id()));
+        }
+        state.request_repaint();
+        shared.get_or_insert_with(|| state.gpu());
+
+        pages.create(&state, handle);
+        self.windows.insert(
+            window.id(),
+            WindowContext {
+                state,
+                pages,
+                is_main,
+            },
+        );
+        Ok(())
+    }
+
+    // 处理 AppEvent::OpenWindow，主窗口还没有创建时（resumed 之前）留给 resumed 一起创建
+    fn open_window(&mut self, event_loop: &ActiveEventLoop, request: Box<WindowRequest>) {
+        let mut pages = Pages::new();
+        if let Err(err) = (request.setup)(&mut pages) {
+            tracing::error!("Failed to set up window pages: {:#}", err);
+            return;
+        }
+        self.pending.push((request.attributes, pages));
+        if !self.windows.is_empty()
+            && let Err(err) = self.create_windows(event_loop)
+        {
+            tracing::error!("Failed to create window: {:#}", err);
+        }
+    }
+
+    // 关闭窗口前询问页面，force 为 true 时（WgpuState::close_window）不询问
+    fn close_window(&mut self, event_loop: &ActiveEventLoop, window_id: WindowId, force: bool) {
+        let Some(context) = self.windows.get_mut(&window_id) else {
+            return;
+        };
+        if context.is_main {
+            // 关闭主窗口时退出事件循环，所以要询问所有窗口的页面
+            let mut allow = true;
+            if !force {
+                for context in self.windows.values_mut() {
+                    allow &= context.pages.close_requested(&context.state);
+                }
+            }
+            if allow {
+                event_loop.exit();
+            }
+        } else if force || context.pages.close_requested(&context.state) {
+            // 关闭次要窗口只销毁该窗口
+            let Some(mut context) = self.windows.remove(&window_id) else {
+                return;
+            };
+            let tasks = context.pages.shutdown(&context.state);
+            context.state.cancellation.cancel();
+            self.wait_for_tasks(tasks);
+            let _ = context.state.device.poll(Maintain::Wait);
+        }
+    }
+
+    // 在事件循环线程上等待页面的任务结束，超时后剩余的任务会被取消
+    fn wait_for_tasks(&self, tasks: impl Future<Output = ()> + Send + 'static) {
+        let Some(handle) = &self.handle else {
+            return;
+        };
+        let timeout = self.shutdown_timeout;
+        let (sender, receiver) = std::sync::mpsc::channel();
+        handle.spawn(async move {
+            if tokio::time::timeout(timeout, tasks).await.is_err() {
+                tracing::warn!("Page tasks did not finish within {:?}, cancelling", timeout);
+            }
+            let _ = sender.send(());
+        });
+        // 运行时已经关闭时任务不会执行，这里也不会一直等下去
+        let _ = receiver.recv_timeout(timeout + Duration::from_millis(100));
+    }
+
+    // 处理 Device 回调和渲染中产生的错误：校验错误交给各窗口的当前页面，无法恢复的错误退出应用
+    fn handle_gpu_errors(&mut self, event_loop: &ActiveEventLoop) {
+        // 所有窗口共享同一个 Device 和错误队列
+        let Some(errors) = self
+            .windows
+            .values()
+            .next()
+            .map(|context| context.state.errors.take())
+        else {
+            return;
+        };
+        for error in errors {
+            if error.is_fatal() {
+                if !event_loop.exiting() {
+                    (self.on_fatal_error)(&error);
+                    event_loop.exit();
+                }
+                continue;
+            }
+            for context in self.windows.values_mut() {
+                context
+                    .pages
+                    .report_error(anyhow::Error::new(error.clone()), &context.state);
+            }
+        }
     }
 }
 
-impl ApplicationHandler for App {
-    fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
-        let res = (|| {
-            let window = event_loop.create_window(WindowAttributes::default())?;
-            let window = Arc::new(window);
-            let state = pollster::block_on(WgpuState::new(window))?;
-            Ok::<WgpuState, anyhow::Error>(state)
-        })();
-        match res {
-            Ok(state) => {
-                self.state.lock().unwrap().replace(state);
-                self.pages.create(self.state.clone());
-            }
-            Err(err) => {
+pub struct AppBuilder {
+    attributes: WindowAttributes,
+    options: GpuOptions,
+    fixed_timestep: Option<Duration>,
+    redraw_mode: RedrawMode,
+    navigation: NavigationUi,
+    shortcuts: NavigationShortcuts,
+    on_fatal_error: FatalErrorHandler,
+    shutdown_timeout: Duration,
+    runtime: Option<RuntimeConfig>,
+}
+
+impl Default for AppBuilder {
+    fn default() -> Self {
+        Self {
+            attributes: Default::default(),
+            options: Default::default(),
+            fixed_timestep: Some(Duration::from_secs(1) / 60),
+            redraw_mode: Default::default(),
+            navigation: Default::default(),
+            shortcuts: Default::default(),
+            shutdown_timeout: Duration::from_secs(2),
+            runtime: None,
+            on_fatal_error: Box::new(|error| {
+                tracing::error!("Fatal GPU error, exiting: {}", error)
+            }),
+        }
+    }
+}
+
+impl AppBuilder {
+    // 直接指定主窗口属性，会覆盖之前设置的标题、尺寸等
+    pub fn window_attributes(mut self, attributes: WindowAttributes) -> Self {
+        self.attributes = attributes;
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.attributes.title = title.into();
+        self
+    }
+
+    pub fn inner_size(mut self, width: u32, height: u32) -> Self {
+        self.attributes.inner_size = Some(PhysicalSize::new(width, height).into());
+        self
+    }
+
+    pub fn window_icon(mut self, icon: Icon) -> Self {
+        self.attributes.window_icon = Some(icon);
+        self
+    }
+
+    // 无边框全屏
+    pub fn fullscreen(mut self, fullscreen: bool) -> Self {
+        self.attributes.fullscreen = fullscreen.then_some(Fullscreen::Borderless(None));
+        self
+    }
+
+    pub fn gpu_options(mut self, options: GpuOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn features(mut self, features: Features) -> Self {
+        self.options.required_features = features;
+        self
+    }
+
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.options.required_limits = limits;
+        self
+    }
+
+    pub fn backends(mut self, backends: Backends) -> Self {
+        self.options.backends = Some(backends);
+        self
+    }
+
+    pub fn power_preference(mut self, power_preference: PowerPreference) -> Self {
+        self.options.power_preference = power_preference;
+        self
+    }
+
+    pub fn present_mode(mut self, present_mode: PresentMode) -> Self {
+        self.options.present_mode = Some(present_mode);
+        self
+    }
+
+    pub fn depth_format(mut self, format: TextureFormat) -> Self {
+        self.options.depth_format = Some(format);
+        self
+    }
+
+    pub fn sample_count(mut self, sample_count: u32) -> Self {
+        self.options.sample_count = sample_count;
+        self
+    }
+
+    // Render::tick 的调用间隔，None 表示不调用
+    pub fn fixed_timestep(mut self, step: Option<Duration>) -> Self {
+        self.fixed_timestep = step.filter(|step| !step.is_zero());
+        self
+    }
+
+    pub fn redraw_mode(mut self, redraw_mode: RedrawMode) -> Self {
+        self.redraw_mode = redraw_mode;
+        self
+    }
+
+    // 主窗口的页面导航界面，其他窗口通过 add_window 返回的 Pages 设置
+    pub fn navigation(mut self, navigation: NavigationUi) -> Self {
+        self.navigation = navigation;
+        self
+    }
+
+    pub fn navigation_shortcuts(mut self, shortcuts: NavigationShortcuts) -> Self {
+        self.shortcuts = shortcuts;
+        self
+    }
+
+    // GPU 内存不足或 Device 丢失时调用，之后应用退出。可以在这里提示用户或保存数据
+    pub fn on_fatal_error(mut self, handler: impl FnMut(&GpuError) + 'static) -> Self {
+        self.on_fatal_error = Box::new(handler);
+        self
+    }
+
+    // 关闭窗口或退出时等待页面未完成任务的最长时间
+    pub fn shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = timeout;
+        self
+    }
+
+    // 使用已经配置好的运行时，App::run 结束时关闭它
+    pub fn runtime(mut self, runtime: Runtime) -> Self {
+        self.runtime = Some(RuntimeConfig::Runtime(runtime));
+        self
+    }
+
+    // 由 App::run 按此配置创建运行时，例如 tokio::runtime::Builder::new_current_thread()
+    pub fn runtime_builder(mut self, builder: tokio::runtime::Builder) -> Self {
+        self.runtime = Some(RuntimeConfig::Builder(builder));
+        self
+    }
+
+    pub fn build(self) -> App {
+        let mut pages = Pages::new();
+        pages.set_navigation(self.navigation);
+        pages.set_navigation_shortcuts(self.shortcuts);
+        App {
+            windows: HashMap::new(),
+            pending: vec![(self.attributes, pages)],
+            options: self.options,
+            fixed_timestep: self.fixed_timestep,
+            redraw_mode: self.redraw_mode,
+            proxy: None,
+            shutdown_timeout: self.shutdown_timeout,
+            on_fatal_error: self.on_fatal_error,
+            cancellation: CancellationToken::new(),
+            runtime: self.runtime,
+            handle: None,
+            startup_error: None,
+        }
+    }
+}
+
+impl ApplicationHandler<AppEvent> for App {
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, event: AppEvent) {
+        match event {
+            AppEvent::Repaint(window_id) => {
+                if let Some(context) = self.windows.get(&window_id)
+                    && let Some(window) = &context.state.window
+                {
+                    window.request_redraw();
+                }
+            }
+            AppEvent::Close(window_id) => self.close_window(event_loop, window_id, true),
+            AppEvent::OpenWindow(request) => self.open_window(event_loop, request),
+        }
+    }
+
+    // 处理完所有事件后，按最近一次需要重绘的时间设置等待方式
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        self.handle_gpu_errors(event_loop);
+
+        let now = Instant::now();
+        let mut wait_until: Option<Instant> = None;
+        for context in self.windows.values_mut() {
+            let state = &mut context.state;
+            match state.next_repaint {
+                Some(next_repaint) if next_repaint <= now => {
+                    state.next_repaint = None;
+                    state.request_repaint();
+                }
+                Some(next_repaint) => {
+                    wait_until = Some(wait_until.map_or(next_repaint, |w| w.min(next_repaint)));
+                }
+                None => {}
+            }
+        }
+        event_loop.set_control_flow(match wait_until {
+            Some(wait_until) => ControlFlow::WaitUntil(wait_until),
+            None => ControlFlow::Wait,
+        });
+    }
+
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        // 从挂起状态恢复时为已有窗口重新创建表面，Device 和页面保持不变
+        for context in self.windows.values_mut() {
+            let state = &mut context.state;
+            let size = PhysicalSize::new(state.config.width, state.config.height);
+            if let Err(err) = state.resume() {
+                tracing::error!("Failed to recreate surface: {}", err);
+                continue;
+            }
+            let new_size = PhysicalSize::new(state.config.width, state.config.height);
+            if new_size != size {
+                context.pages.resize(new_size, state);
+            }
+            context.pages.resume(state);
+            state.request_repaint();
+        }
+        if let Err(err) = self.create_windows(event_loop) {
+            // 主窗口创建失败（例如请求的特性不可用）时退出，并由 run 返回错误
+            if self.windows.is_empty() {
+                self.startup_error = Some(err.context("Failed to create the main window"));
+                event_loop.exit();
+            } else {
                 tracing::error!("Failed to create WgpuState: {}", err);
             }
         }
     }
 
+    // 挂起时只释放表面，下一次 resumed 时重新创建
+    fn suspended(&mut self, _event_loop: &ActiveEventLoop) {
+        for context in self.windows.values_mut() {
+            context.pages.suspend(&context.state);
+            context.state.suspend();
+        }
+    }
+
+    // 事件循环退出前按顺序关闭：销毁页面并取消所有任务，等待进行中的任务结束（有超时），
+    // GPU 完成已提交的工作后再销毁窗口
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        let tasks: Vec<_> = self
+            .windows
+            .values_mut()
+            .map(|context| context.pages.shutdown(&context.state))
+            .collect();
+        self.cancellation.cancel();
+        self.wait_for_tasks(async move {
+            for task in tasks {
+                task.await;
+            }
+        });
+        // 所有窗口共享同一个 Device
+        if let Some(context) = self.windows.values().next() {
+            let _ = context.state.device.poll(Maintain::Wait);
+        }
+        self.windows.clear();
+    }
+
     fn window_event(
         &mut self,
-        event_loop: &winit::event_loop::ActiveEventLoop,
-        _window_id: winit::window::WindowId,
+        event_loop: &ActiveEventLoop,
+        window_id: WindowId,
         event: winit::event::WindowEvent,
     ) {
-        if let Some(mut state) = self.state.lock().unwrap().as_mut() {
-            state.egui_renderer.handle_input(&state.window, &event);
+        // 按 WindowId 把事件分发给对应的窗口
+        let Some(context) = self.windows.get_mut(&window_id) else {
+            return;
+        };
 
-            match event {
-                // 关闭窗口请求
-                WindowEvent::CloseRequested => {
-                    event_loop.exit(); // 退出事件循环
-                }
+        if matches!(event, WindowEvent::CloseRequested) {
+            self.close_window(event_loop, window_id, false);
+            return;
+        }
+
+        let state = &mut context.state;
+        let Some(window) = state.window.clone() else {
+            return;
+        };
+        // 输入事件需要 egui 重新布局，窗口大小变化需要重新绘制
+        if state.egui_renderer.handle_input(&window, &event)
+            || matches!(
+                event,
+                WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. }
+            )
+        {
+            window.request_redraw();
+        }
+
+        match event {
+            // 重绘请求（驱动渲染循环）
+            WindowEvent::RedrawRequested => {
+                // 执行窗口预呈现通知
+                window.pre_present_notify();
 
-                // 重绘请求（驱动渲染循环）
-                WindowEvent::RedrawRequested => {
-                    // 执行窗口预呈现通知
-                    state.window.pre_present_notify();
-
-                    // 执行实际渲染操作
-                    if let Err(r) = (|| {
-                        ui_render(&mut state, &mut self.pages)?;
-                        Ok::<(), anyhow::Error>(())
-                    })() {
-                        tracing::error!("Render error: {}", r);
+                // 在事件循环线程上处理页面消息，之后再绘制 UI
+                context.pages.process_messages(state);
+
+                // 执行实际渲染操作
+                let rendered = match ui_render(state, &mut context.pages) {
+                    Ok(rendered) => rendered,
+                    // 表面内存不足等 GPU 错误交给 about_to_wait 统一处理
+                    Err(r) => {
+                        match r.downcast::<GpuError>() {
+                            Ok(error) => state.errors.push(error),
+                            Err(r) => tracing::error!("Render error: {}", r),
+                        }
+                        false
                     }
+                };
 
-                    // 请求下一帧重绘（维持持续渲染）
-                    state.window.request_redraw();
+                // 持续渲染模式下立即请求下一帧，否则按 egui 的要求安排下一次重绘；
+                // 挂起期间不再请求，跳过的帧由 next_repaint 决定何时重试
+                let continuous =
+                    self.redraw_mode == RedrawMode::Continuous || context.pages.continuous_redraw();
+                if state.is_suspended() {
+                    state.next_repaint = None;
+                } else if (continuous && rendered)
+                    || state
+                        .next_repaint
+                        .is_some_and(|next_repaint| next_repaint <= Instant::now())
+                {
+                    state.next_repaint = None;
+                    window.request_redraw();
                 }
+            }
 
-                // 窗口大小变化事件
-                WindowEvent::Resized(size) => {
-                    // 更新WGPU表面配置
-                    state.resize(size);
-                    tracing::info!("Window resized to {:?}", size);
+            // F12 截图并保存为 PNG
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(KeyCode::F12),
+                        state: ElementState::Pressed,
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } => {
+                if let Some(handle) = &self.handle {
+                    let capture = state.capture_frame();
+                    handle.spawn(async move {
+                        let timestamp = SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .unwrap_or_default()
+                            .as_millis();
+                        let path = format!("screenshot-{}.png", timestamp);
+                        match capture.await.and_then(|frame| frame.save_png(&path)) {
+                            Ok(()) => tracing::info!("Screenshot saved to {}", path),
+                            Err(err) => tracing::error!("Failed to capture screenshot: {}", err),
+                        }
+                    });
                 }
+            }
 
-                // 其他未处理事件
-                _ => {}
+            // 窗口大小变化事件
+            WindowEvent::Resized(size) => {
+                // 更新WGPU表面配置
+                state.resize(size);
+                context.pages.resize(size, state);
+                tracing::info!("Window resized to {:?}", size);
             }
 
-            self.pages.handle_event(event, state);
+            // 其他未处理事件
+            _ => {}
         }
+
+        context.pages.handle_event(event, state);
     }
 }
 
-fn ui_render(state: &mut WgpuState, ui: &mut dyn Render) -> anyhow::Result<()> {
-    let surface_texture = state.surface.get_current_texture()?;
-    let view = surface_texture
-        .texture
-        .create_view(&TextureViewDescriptor::default());
+// 给仍在运行的任务（例如截图保存）一点时间结束。
+// 在异步上下文中（例如在 #[tokio::main] 中传入了自己的运行时）不允许阻塞等待，只能在后台关闭
+fn shutdown_runtime(runtime: Runtime, timeout: Duration) {
+    if Handle::try_current().is_ok() {
+        runtime.shutdown_background();
+    } else {
+        runtime.shutdown_timeout(timeout);
+    }
+}
+
+// 返回是否渲染了新的一帧
+pub(crate) fn ui_render(state: &mut WgpuState, ui: &mut dyn Render) -> anyhow::Result<bool> {
+    // 挂起期间没有表面，恢复后会重新请求重绘
+    if state.is_suspended() {
+        return Ok(false);
+    }
+    let Some((surface_texture, view)) = state.current_target()? else {
+        return Ok(false);
+    };
     let screen_descriptor = ScreenDescriptor {
         size_in_pixels: [state.config.width, state.config.height],
-        pixels_per_point: state.window.scale_factor() as f32,
+        pixels_per_point: state.scale_factor() as f32,
     };
-    let mut encoder = state
+    let encoder = state
         .device
         .create_command_encoder(&CommandEncoderDescriptor::default());
+    state.frame_timer.tick();
 
-    ui.render(&state, &view, &mut encoder)?;
+    // 固定步长更新与渲染分离，模拟速度不受帧率影响
+    if let Some(step) = state.frame_timer.fixed_timestep {
+        for _ in 0..state.frame_timer.fixed_steps() {
+            ui.tick(step, state);
+        }
+    }
 
-    {
-        state.egui_renderer.begin_frame(&state.window);
+    let mut frame = FrameContext::new(state, &view, encoder, Vec::new());
+    ui.render_frame(&mut frame)?;
+    let (encoder, command_buffers) = frame.finish();
+
+    let (mut encoder, command_buffers) = {
+        let window = state.window.clone();
+        state
+            .egui_renderer
+            .begin_frame(window.as_deref(), &screen_descriptor);
 
-        ui.ui_draw(state.egui_renderer.context());
+        let ctx = state.egui_renderer.context().clone();
+        let frame = FrameContext::new(state, &view, encoder, command_buffers);
+        ui.ui_frame(&ctx, &frame);
+        let (mut encoder, command_buffers) = frame.finish();
+        shader::show_errors(&ctx, state);
 
-        state.egui_renderer.end_frame_and_draw(
+        let repaint_delay = state.egui_renderer.end_frame_and_draw(
             &state.device,
             &state.queue,
             &mut encoder,
-            &state.window,
+            window.as_deref(),
             &view,
             screen_descriptor,
         );
-    }
+        state.next_repaint = Instant::now().checked_add(repaint_delay);
+        (encoder, command_buffers)
+    };
 
-    // 7. 提交命令到队列
+    // 有截图请求时，在提交前把当前帧复制到缓冲区
+    let captures = std::mem::take(&mut *state.pending_captures.lock().unwrap());
+    let readback = if captures.is_empty() {
+        None
+    } else {
+        let texture = surface_texture
+            .as_ref()
+            .map(|surface_texture| &surface_texture.texture)
+            .or(state.offscreen.as_ref())
+            .ok_or(anyhow::anyhow!("No render target to capture"))?;
+        Some(Readback::copy(&state.device, &mut encoder, texture))
+    };
+
+    // 7. 提交命令到队列（页面额外提交的命令缓冲区在前）
     let command_buffer = encoder.finish();
-    state.queue.submit(std::iter::once(command_buffer));
+    state.queue.submit(
+        command_buffers
+            .into_iter()
+            .chain(std::iter::once(command_buffer)),
+    );
+
+    if let Some(readback) = readback {
+        let result = readback.and_then(|readback| readback.finish(&state.device));
+        for capture in captures {
+            let result = match &result {
+                Ok(frame) => Ok(frame.clone()),
+                Err(err) => Err(anyhow::anyhow!("{}", err)),
+            };
+            let _ = capture.send(result);
+        }
+    }
+
+    // 8. 呈现渲染结果（headless 模式下结果保留在 offscreen 纹理中）
+    if let Some(surface_texture) = surface_texture {
+        surface_texture.present();
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // 8. 呈现渲染结果
-    surface_texture.present();
-    Ok(())
+    // 在 #[tokio::main] 中通过 AppBuilder::runtime 传入运行时，run 结束时不能 panic
+    #[tokio::test(flavor = "multi_thread")]
+    async fn owned_runtime_shuts_down_inside_async_context() {
+        for runtime in [
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap(),
+            tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+                .unwrap(),
+        ] {
+            runtime.spawn(tokio::time::sleep(Duration::from_secs(60)));
+            shutdown_runtime(runtime, Duration::from_secs(1));
+        }
+    }
 }
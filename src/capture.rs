@@ -0,0 +1,186 @@
+use anyhow::{anyhow, bail};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+};
+use wgpu::{Buffer, CommandEncoder, Device, Texture, TextureFormat};
+
+// 截取到的一帧画面，像素为 sRGB 编码的 RGBA8
+#[derive(Clone)]
+pub struct FrameCapture {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+impl FrameCapture {
+    pub fn save_png(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(file, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.rgba)?;
+        Ok(())
+    }
+
+    pub fn load_png(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let mut decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf)?;
+        let data = &buf[..info.buffer_size()];
+
+        let rgba = match info.color_type {
+            png::ColorType::Rgba => data.to_vec(),
+            png::ColorType::Rgb => data
+                .chunks(3)
+                .flat_map(|p| [p[0], p[1], p[2], 255])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => data
+                .chunks(2)
+                .flat_map(|p| [p[0], p[0], p[0], p[1]])
+                .collect(),
+            png::ColorType::Grayscale => data.iter().flat_map(|&p| [p, p, p, 255]).collect(),
+            png::ColorType::Indexed => bail!("Indexed PNG images are not supported"),
+        };
+
+        Ok(Self {
+            width: info.width,
+            height: info.height,
+            rgba,
+        })
+    }
+}
+
+// 将纹理复制到可映射的缓冲区，提交命令后调用 finish 读取
+pub(crate) struct Readback {
+    buffer: Buffer,
+    format: TextureFormat,
+    width: u32,
+    height: u32,
+    padded_bytes_per_row: u32,
+}
+
+impl Readback {
+    pub(crate) fn copy(
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        texture: &Texture,
+    ) -> anyhow::Result<Self> {
+        let format = texture.format();
+        let bytes_per_pixel = match format {
+            TextureFormat::Rgba8Unorm
+            | TextureFormat::Rgba8UnormSrgb
+            | TextureFormat::Bgra8Unorm
+            | TextureFormat::Bgra8UnormSrgb => 4,
+            TextureFormat::Rgba16Float => 8,
+            _ => bail!("Unsupported texture format for capture: {:?}", format),
+        };
+        if !texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+            bail!("Texture does not support COPY_SRC, capture is not available");
+        }
+
+        let (width, height) = (texture.width(), texture.height());
+        // 每行字节数需要按 256 对齐
+        let padded_bytes_per_row = (width * bytes_per_pixel)
+            .div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+            * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("capture_buffer"),
+            size: (padded_bytes_per_row * height) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            texture.size(),
+        );
+
+        Ok(Self {
+            buffer,
+            format,
+            width,
+            height,
+            padded_bytes_per_row,
+        })
+    }
+
+    // 阻塞等待 GPU 完成复制并转换为 RGBA8
+    pub(crate) fn finish(self, device: &Device) -> anyhow::Result<FrameCapture> {
+        let slice = self.buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |res| {
+            let _ = sender.send(res);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .map_err(|_| anyhow!("Capture buffer was never mapped"))??;
+
+        let data = slice.get_mapped_range();
+        let mut rgba = Vec::with_capacity((self.width * self.height * 4) as usize);
+        for row in data.chunks(self.padded_bytes_per_row as usize) {
+            match self.format {
+                TextureFormat::Rgba16Float => {
+                    for pixel in row[..(self.width * 8) as usize].chunks(8) {
+                        for (i, c) in pixel.chunks(2).enumerate() {
+                            let value = f16_to_f32(u16::from_le_bytes([c[0], c[1]]));
+                            // 浮点格式为线性空间，alpha 通道不需要转换
+                            let value = if i < 3 { linear_to_srgb(value) } else { value };
+                            rgba.push((value.clamp(0.0, 1.0) * 255.0).round() as u8);
+                        }
+                    }
+                }
+                // 8 位格式中存储的已经是最终显示的值（sRGB 格式由 GPU 编码），只需调整通道顺序
+                TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => {
+                    for pixel in row[..(self.width * 4) as usize].chunks(4) {
+                        rgba.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]]);
+                    }
+                }
+                _ => rgba.extend_from_slice(&row[..(self.width * 4) as usize]),
+            }
+        }
+        drop(data);
+        self.buffer.unmap();
+
+        Ok(FrameCapture {
+            width: self.width,
+            height: self.height,
+            rgba,
+        })
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f32;
+    match exponent {
+        0 => sign * mantissa * 2f32.powi(-24),
+        31 if mantissa == 0.0 => sign * f32::INFINITY,
+        31 => f32::NAN,
+        _ => sign * (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
@@ -0,0 +1,120 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::{runtime::Handle, task::JoinSet};
+
+type Action<M> = Pin<Box<dyn Future<Output = anyhow::Result<M>> + Send>>;
+
+// Page::update 返回的异步操作，由框架在 tokio 运行时上执行，结果作为消息交回页面
+pub struct Command<M> {
+    actions: Vec<Action<M>>,
+}
+
+impl<M: Send + 'static> Command<M> {
+    pub fn none() -> Self {
+        Self {
+            actions: Vec::new(),
+        }
+    }
+
+    // 立即把消息交回页面，会在下一次处理消息时调用 update
+    pub fn message(message: M) -> Self {
+        Self::perform(async move { message }, |message| message)
+    }
+
+    pub fn perform<T, F>(future: F, map: impl FnOnce(T) -> M + Send + 'static) -> Self
+    where
+        F: Future<Output = T> + Send + 'static,
+    {
+        Self {
+            actions: vec![Box::pin(async move { Ok(map(future.await)) })],
+        }
+    }
+
+    // 失败时错误会交给 Page::on_error
+    pub fn try_perform<T, E, F>(future: F, map: impl FnOnce(T) -> M + Send + 'static) -> Self
+    where
+        F: Future<Output = Result<T, E>> + Send + 'static,
+        E: Into<anyhow::Error>,
+    {
+        Self {
+            actions: vec![Box::pin(async move {
+                future.await.map(map).map_err(Into::into)
+            })],
+        }
+    }
+
+    pub fn batch(commands: impl IntoIterator<Item = Command<M>>) -> Self {
+        Self {
+            actions: commands
+                .into_iter()
+                .flat_map(|command| command.actions)
+                .collect(),
+        }
+    }
+
+    // 把子组件的命令转换为页面自己的消息类型
+    pub fn map<N: Send + 'static>(self, f: impl Fn(M) -> N + Clone + Send + 'static) -> Command<N> {
+        Command {
+            actions: self
+                .actions
+                .into_iter()
+                .map(|action| {
+                    let f = f.clone();
+                    Box::pin(async move { action.await.map(f) }) as Action<N>
+                })
+                .collect(),
+        }
+    }
+
+    pub fn is_none(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+impl<M: Send + 'static> Default for Command<M> {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+// 页面正在执行的命令，drop 时取消所有未完成的任务
+pub(crate) struct CommandQueue<M> {
+    tasks: JoinSet<anyhow::Result<M>>,
+    handle: Handle,
+}
+
+impl<M: Send + 'static> CommandQueue<M> {
+    pub(crate) fn new(handle: Handle) -> Self {
+        Self {
+            tasks: JoinSet::new(),
+            handle,
+        }
+    }
+
+    pub(crate) fn spawn(&mut self, command: Command<M>) {
+        for action in command.actions {
+            self.tasks.spawn_on(action, &self.handle);
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    // 等待所有命令结束，结果被丢弃；future 被 drop 时取消剩余的命令
+    pub(crate) async fn finish(mut self) {
+        while self.tasks.join_next().await.is_some() {}
+    }
+
+    // 返回一个已完成命令的结果，任务 panic 时也转换为错误
+    pub(crate) fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<anyhow::Result<M>>> {
+        self.tasks.poll_join_next(cx).map(|result| {
+            result.map(|result| {
+                result.unwrap_or_else(|err| Err(anyhow::anyhow!("Command task failed: {}", err)))
+            })
+        })
+    }
+}
@@ -1,19 +1,24 @@
 use egui::Context;
 use egui_wgpu::{Renderer, ScreenDescriptor};
 use egui_winit::State;
+use std::time::Duration;
 use wgpu::{CommandEncoder, Device, Queue, TextureFormat, TextureView};
 use winit::event::WindowEvent;
 use winit::window::Window;
 
 pub struct EguiRenderer {
-    pub state: State,
+    pub context: Context,
+    // 无窗口（headless）模式下为 None
+    pub state: Option<State>,
+    // headless 模式下由外部注入的输入事件，在下一帧开始时交给 egui
+    pub pending_events: Vec<egui::Event>,
     pub renderer: Renderer,
     pub frame_started: bool,
 }
 
 impl EguiRenderer {
     pub fn context(&self) -> &egui::Context {
-        self.state.egui_ctx()
+        &self.context
     }
 
     pub fn new(
@@ -21,17 +26,19 @@ impl EguiRenderer {
         output_color_format: TextureFormat,
         output_depth_format: Option<TextureFormat>,
         msaa_samples: u32,
-        window: &Window,
+        window: Option<&Window>,
     ) -> Self {
-        let egui_ctx = Context::default();
-        let state = State::new(
-            egui_ctx,
-            egui::ViewportId::ROOT,
-            &window,
-            Some(window.scale_factor() as f32),
-            None,
-            Some(2 * 1024),
-        );
+        let context = Context::default();
+        let state = window.map(|window| {
+            State::new(
+                context.clone(),
+                egui::ViewportId::ROOT,
+                window,
+                Some(window.scale_factor() as f32),
+                None,
+                Some(2 * 1024),
+            )
+        });
 
         let renderer = Renderer::new(
             device,
@@ -42,23 +49,42 @@ impl EguiRenderer {
         );
 
         Self {
+            context,
             state,
+            pending_events: Vec::new(),
             renderer,
             frame_started: false,
         }
     }
 
-    pub fn handle_input(&mut self, window: &Window, event: &WindowEvent) {
-        let _ = self.state.on_window_event(window, event);
+    // 返回 egui 是否需要因为该事件重绘
+    pub fn handle_input(&mut self, window: &Window, event: &WindowEvent) -> bool {
+        match self.state.as_mut() {
+            Some(state) => state.on_window_event(window, event).repaint,
+            None => false,
+        }
     }
 
     pub fn ppp(&mut self, v: f32) {
         self.context().set_pixels_per_point(v);
     }
 
-    pub fn begin_frame(&mut self, window: &Window) {
-        let raw_input = self.state.take_egui_input(window);
-        self.state.egui_ctx().begin_pass(raw_input);
+    pub fn begin_frame(&mut self, window: Option<&Window>, screen_descriptor: &ScreenDescriptor) {
+        let raw_input = match (self.state.as_mut(), window) {
+            (Some(state), Some(window)) => state.take_egui_input(window),
+            // headless 模式下没有 winit 输入，只提供屏幕区域
+            _ => {
+                let [width, height] = screen_descriptor.size_in_pixels;
+                let size =
+                    egui::vec2(width as f32, height as f32) / screen_descriptor.pixels_per_point;
+                egui::RawInput {
+                    screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, size)),
+                    events: std::mem::take(&mut self.pending_events),
+                    ..Default::default()
+                }
+            }
+        };
+        self.context.begin_pass(raw_input);
         self.frame_started = true;
     }
 
@@ -67,25 +93,31 @@ impl EguiRenderer {
         device: &Device,
         queue: &Queue,
         encoder: &mut CommandEncoder,
-        window: &Window,
+        window: Option<&Window>,
         window_surface_view: &TextureView,
         screen_descriptor: ScreenDescriptor,
-    ) {
+    ) -> Duration {
         if !self.frame_started {
             panic!("Frame not started");
         }
 
         self.ppp(screen_descriptor.pixels_per_point);
 
-        let full_output = self.state.egui_ctx().end_pass();
+        let full_output = self.context.end_pass();
+        // egui 希望多久之后再次重绘，Duration::MAX 表示不需要
+        let repaint_delay = full_output
+            .viewport_output
+            .get(&egui::ViewportId::ROOT)
+            .map(|viewport| viewport.repaint_delay)
+            .unwrap_or(Duration::MAX);
 
-        self.state
-            .handle_platform_output(window, full_output.platform_output);
+        if let (Some(state), Some(window)) = (self.state.as_mut(), window) {
+            state.handle_platform_output(window, full_output.platform_output);
+        }
 
         let tris = self
-            .state
-            .egui_ctx()
-            .tessellate(full_output.shapes, self.state.egui_ctx().pixels_per_point());
+            .context
+            .tessellate(full_output.shapes, self.context.pixels_per_point());
 
         for (id, image_delta) in full_output.textures_delta.set {
             self.renderer
@@ -116,5 +148,6 @@ impl EguiRenderer {
         }
 
         self.frame_started = false;
+        repaint_delay
     }
 }
@@ -0,0 +1,81 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+use wgpu::{Device, DeviceLostReason};
+
+// GPU 相关的错误，可以从 anyhow::Error 中通过 downcast_ref 取出
+#[derive(Clone, Debug)]
+pub enum GpuError {
+    // 表面或 Device 内存不足
+    OutOfMemory,
+    // 驱动重置、GPU 被移除或 Device 被销毁
+    DeviceLost {
+        reason: DeviceLostReason,
+        message: String,
+    },
+    // 没有被 error scope 捕获的校验错误，会交给当前页面的 on_error
+    Validation(String),
+    Internal(String),
+}
+
+impl GpuError {
+    // 无法恢复的错误，App 会调用 AppBuilder::on_fatal_error 设置的回调后退出
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, GpuError::OutOfMemory | GpuError::DeviceLost { .. })
+    }
+}
+
+impl fmt::Display for GpuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpuError::OutOfMemory => write!(f, "GPU out of memory"),
+            GpuError::DeviceLost { reason, message } => {
+                write!(f, "GPU device lost ({:?}): {}", reason, message)
+            }
+            GpuError::Validation(description) => write!(f, "GPU validation error: {}", description),
+            GpuError::Internal(description) => write!(f, "GPU internal error: {}", description),
+        }
+    }
+}
+
+impl std::error::Error for GpuError {}
+
+// Device 回调中产生的错误，在事件循环线程上统一取出处理。
+// 多个窗口共享同一个 Device，所以也共享同一个队列
+#[derive(Clone, Default)]
+pub(crate) struct GpuErrors(Arc<Mutex<Vec<GpuError>>>);
+
+impl GpuErrors {
+    // 注册回调后 wgpu 不再因为校验错误直接 panic
+    pub(crate) fn install(device: &Device) -> Self {
+        let errors = Self::default();
+        device.on_uncaptured_error(Box::new({
+            let errors = errors.clone();
+            move |error| {
+                errors.push(match error {
+                    wgpu::Error::OutOfMemory { .. } => GpuError::OutOfMemory,
+                    wgpu::Error::Validation { description, .. } => {
+                        GpuError::Validation(description)
+                    }
+                    wgpu::Error::Internal { description, .. } => GpuError::Internal(description),
+                })
+            }
+        }));
+        device.set_device_lost_callback({
+            let errors = errors.clone();
+            move |reason, message| errors.push(GpuError::DeviceLost { reason, message })
+        });
+        errors
+    }
+
+    // 这里只记 debug 日志：普通错误由页面的 on_error 记录，致命错误由 on_fatal_error 记录
+    pub(crate) fn push(&self, error: GpuError) {
+        tracing::debug!("{}", error);
+        self.0.lock().unwrap().push(error);
+    }
+
+    pub(crate) fn take(&self) -> Vec<GpuError> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
@@ -0,0 +1,84 @@
+use crate::page::Pages;
+use std::fmt;
+use winit::{
+    event_loop::EventLoopProxy,
+    window::{WindowAttributes, WindowId},
+};
+
+// 通过 EventLoopProxy 发送给事件循环的用户事件
+#[derive(Debug)]
+pub enum AppEvent {
+    // 请求重绘指定窗口
+    Repaint(WindowId),
+    // 关闭指定窗口，不经过页面的 on_close_requested
+    Close(WindowId),
+    // 在运行中打开一个新窗口
+    OpenWindow(Box<WindowRequest>),
+}
+
+type SetupPages = Box<dyn FnOnce(&mut Pages) -> anyhow::Result<()> + Send>;
+
+// 新窗口的属性和注册页面的回调。Pages 只能在事件循环线程上创建，所以传入的是回调
+pub struct WindowRequest {
+    pub attributes: WindowAttributes,
+    pub(crate) setup: SetupPages,
+}
+
+impl WindowRequest {
+    pub fn new(
+        attributes: WindowAttributes,
+        setup: impl FnOnce(&mut Pages) -> anyhow::Result<()> + Send + 'static,
+    ) -> Self {
+        Self {
+            attributes,
+            setup: Box::new(setup),
+        }
+    }
+}
+
+impl fmt::Debug for WindowRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WindowRequest")
+            .field("attributes", &self.attributes)
+            .finish_non_exhaustive()
+    }
+}
+
+// 可在任意线程（例如 tokio 任务）中请求重绘某个窗口
+#[derive(Clone)]
+pub struct RepaintHandle {
+    proxy: EventLoopProxy<AppEvent>,
+    window_id: WindowId,
+}
+
+impl RepaintHandle {
+    pub(crate) fn new(proxy: EventLoopProxy<AppEvent>, window_id: WindowId) -> Self {
+        Self { proxy, window_id }
+    }
+
+    pub fn window_id(&self) -> WindowId {
+        self.window_id
+    }
+
+    pub fn request_repaint(&self) {
+        // 事件循环已退出时忽略
+        let _ = self.proxy.send_event(AppEvent::Repaint(self.window_id));
+    }
+
+    pub(crate) fn request_close(&self) {
+        let _ = self.proxy.send_event(AppEvent::Close(self.window_id));
+    }
+
+    // 打开新窗口，setup 在事件循环线程上调用，用于注册页面和设置初始页面
+    pub fn open_window(
+        &self,
+        attributes: WindowAttributes,
+        setup: impl FnOnce(&mut Pages) -> anyhow::Result<()> + Send + 'static,
+    ) {
+        let _ = self
+            .proxy
+            .send_event(AppEvent::OpenWindow(Box::new(WindowRequest::new(
+                attributes, setup,
+            ))));
+    }
+}
@@ -0,0 +1,222 @@
+use crate::WgpuState;
+use std::time::{Duration, Instant};
+use wgpu::{CommandBuffer, CommandEncoder, TextureView};
+use winit::dpi::PhysicalSize;
+
+// 每帧传给页面的上下文，由 app::ui_render 构建
+pub struct FrameContext<'a> {
+    pub state: &'a WgpuState,
+    // 最终的渲染目标（表面或 offscreen 纹理），启用 MSAA 时作为解析目标
+    pub view: &'a TextureView,
+    pub encoder: CommandEncoder,
+    pub frame_index: u64,
+    // 距上一帧的时间
+    pub delta: Duration,
+    // 距第一帧的时间
+    pub elapsed: Duration,
+    pub size: PhysicalSize<u32>,
+    pub scale_factor: f64,
+    // 固定步长更新的插值系数：剩余累计时间 / 步长，范围 [0, 1)
+    pub alpha: f32,
+    pub(crate) command_buffers: Vec<CommandBuffer>,
+}
+
+impl<'a> FrameContext<'a> {
+    pub(crate) fn new(
+        state: &'a WgpuState,
+        view: &'a TextureView,
+        encoder: CommandEncoder,
+        command_buffers: Vec<CommandBuffer>,
+    ) -> Self {
+        let timer = &state.frame_timer;
+        Self {
+            state,
+            view,
+            encoder,
+            frame_index: timer.index,
+            delta: timer.delta,
+            elapsed: timer.elapsed(),
+            size: PhysicalSize::new(state.config.width, state.config.height),
+            scale_factor: state.scale_factor(),
+            alpha: timer.alpha(),
+            command_buffers,
+        }
+    }
+
+    pub fn depth_view(&self) -> Option<&'a TextureView> {
+        self.state.depth_view.as_ref()
+    }
+
+    pub fn msaa_view(&self) -> Option<&'a TextureView> {
+        self.state.msaa_view.as_ref()
+    }
+
+    pub fn color_attachment(
+        &self,
+        ops: wgpu::Operations<wgpu::Color>,
+    ) -> wgpu::RenderPassColorAttachment<'a> {
+        self.state.color_attachment(self.view, ops)
+    }
+
+    pub fn depth_stencil_attachment(
+        &self,
+        depth_ops: Option<wgpu::Operations<f32>>,
+        stencil_ops: Option<wgpu::Operations<u32>>,
+    ) -> Option<wgpu::RenderPassDepthStencilAttachment<'a>> {
+        self.state.depth_stencil_attachment(depth_ops, stencil_ops)
+    }
+
+    // 额外的命令缓冲区，会在本帧主 encoder 之前提交
+    pub fn submit(&mut self, command_buffer: CommandBuffer) {
+        self.command_buffers.push(command_buffer);
+    }
+
+    pub(crate) fn finish(self) -> (CommandEncoder, Vec<CommandBuffer>) {
+        (self.encoder, self.command_buffers)
+    }
+}
+
+// 单帧内最多执行的固定步长更新次数，避免卡顿后陷入追赶循环
+const MAX_FIXED_STEPS: u32 = 8;
+
+pub(crate) struct FrameTimer {
+    start: Instant,
+    last: Option<Instant>,
+    index: u64,
+    delta: Duration,
+    pub(crate) fixed_timestep: Option<Duration>,
+    accumulator: Duration,
+    // 设置后每帧按这个间隔推进时间而不是读取墙钟，headless 渲染的结果因此可以复现
+    pub(crate) frame_delta: Option<Duration>,
+}
+
+impl FrameTimer {
+    pub(crate) fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            last: None,
+            index: 0,
+            delta: Duration::ZERO,
+            fixed_timestep: Some(Duration::from_secs(1) / 60),
+            accumulator: Duration::ZERO,
+            frame_delta: None,
+        }
+    }
+
+    // 根据本帧的时间间隔返回需要执行的固定步长更新次数
+    pub(crate) fn fixed_steps(&mut self) -> u32 {
+        let Some(step) = self.fixed_timestep else {
+            return 0;
+        };
+        self.accumulator += self.delta;
+        let steps = self.accumulator.as_nanos() / step.as_nanos();
+        // 只保留不足一个步长的部分，超出上限的时间直接丢弃
+        self.accumulator =
+            Duration::from_nanos((self.accumulator.as_nanos() % step.as_nanos()) as u64);
+        if steps > MAX_FIXED_STEPS as u128 {
+            tracing::warn!("Skipping {} fixed updates", steps - MAX_FIXED_STEPS as u128);
+            return MAX_FIXED_STEPS;
+        }
+        steps as u32
+    }
+
+    fn alpha(&self) -> f32 {
+        match self.fixed_timestep {
+            Some(step) => self.accumulator.as_secs_f32() / step.as_secs_f32(),
+            None => 0.0,
+        }
+    }
+
+    // 每帧开始时调用一次
+    pub(crate) fn tick(&mut self) {
+        let now = match (self.last, self.frame_delta) {
+            (Some(last), Some(delta)) => last + delta,
+            _ => Instant::now(),
+        };
+        match self.last {
+            Some(last) => {
+                self.delta = now - last;
+                self.index += 1;
+            }
+            None => self.start = now,
+        }
+        self.last = Some(now);
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.last.map(|last| last - self.start).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEP: Duration = Duration::from_millis(10);
+
+    fn timer() -> FrameTimer {
+        let mut timer = FrameTimer::new();
+        timer.fixed_timestep = Some(STEP);
+        timer
+    }
+
+    fn advance(timer: &mut FrameTimer, delta: Duration) -> u32 {
+        timer.delta = delta;
+        timer.fixed_steps()
+    }
+
+    #[test]
+    fn steps_accumulate_across_frames() {
+        let mut timer = timer();
+        assert_eq!(advance(&mut timer, Duration::from_millis(4)), 0);
+        assert_eq!(advance(&mut timer, Duration::from_millis(4)), 0);
+        assert_eq!(advance(&mut timer, Duration::from_millis(4)), 1);
+        assert_eq!(timer.accumulator, Duration::from_millis(2));
+        assert_eq!(advance(&mut timer, Duration::from_millis(35)), 3);
+        assert_eq!(timer.accumulator, Duration::from_millis(7));
+    }
+
+    #[test]
+    fn long_pause_is_clamped_and_drained() {
+        let mut timer = timer();
+        assert_eq!(advance(&mut timer, Duration::from_secs(5)), MAX_FIXED_STEPS);
+        assert!(timer.accumulator < STEP);
+        // 暂停之后的下一帧不应该继续追赶
+        assert_eq!(advance(&mut timer, Duration::from_millis(5)), 0);
+    }
+
+    #[test]
+    fn alpha_stays_below_one() {
+        let mut timer = timer();
+        advance(&mut timer, Duration::from_millis(15));
+        assert!((timer.alpha() - 0.5).abs() < 1e-4);
+        advance(&mut timer, Duration::from_secs(3));
+        assert!((0.0..1.0).contains(&timer.alpha()));
+    }
+
+    #[test]
+    fn fixed_frame_delta_ignores_wall_clock() {
+        let mut timer = timer();
+        timer.frame_delta = Some(Duration::from_millis(25));
+        let steps: Vec<u32> = (0..4)
+            .map(|_| {
+                timer.tick();
+                std::thread::sleep(Duration::from_millis(1));
+                timer.fixed_steps()
+            })
+            .collect();
+        assert_eq!(steps, [0, 2, 3, 2]);
+        assert_eq!(timer.index, 3);
+        assert_eq!(timer.delta, Duration::from_millis(25));
+        assert_eq!(timer.elapsed(), Duration::from_millis(75));
+        assert!((timer.alpha() - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn no_steps_without_fixed_timestep() {
+        let mut timer = timer();
+        timer.fixed_timestep = None;
+        assert_eq!(advance(&mut timer, Duration::from_secs(1)), 0);
+        assert_eq!(timer.alpha(), 0.0);
+    }
+}
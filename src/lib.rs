@@ -1,20 +1,50 @@
 mod app;
+mod capture;
+mod command;
 mod egui_utils;
+mod error;
+mod event;
+mod frame;
+mod navigation;
+mod navigator;
 mod page;
+mod route;
+mod shader;
 mod state;
-pub use app::App;
+mod subscription;
+pub mod testing;
+pub use app::{App, AppBuilder, RedrawMode, WindowContext};
+pub use capture::FrameCapture;
+pub use command::Command;
 pub use egui;
-pub use page::Page;
-pub use state::WgpuState;
+pub use error::GpuError;
+pub use event::{AppEvent, RepaintHandle, WindowRequest};
+pub use frame::FrameContext;
+pub use navigation::{NavigationShortcuts, NavigationUi};
+pub use navigator::Navigator;
+pub use notify;
+pub use page::{Page, PageInfo, PagePolicy, Pages};
+pub use route::{Params, Route};
+pub use shader::{ShaderAsset, ShaderError, ShaderPipeline};
+pub use state::{GpuContext, GpuOptions, WgpuState};
+use std::time::Duration;
+pub use subscription::Subscription;
+pub use tokio_util::sync::CancellationToken;
 pub use wgpu;
 use wgpu::{CommandEncoder, TextureView};
 pub use winit;
 
 pub trait Render {
+    // 旧的绘制 UI 接口，默认由 ui_frame 调用；需要帧信息时改为实现 ui_frame
     fn ui_draw(&mut self, ctx: &egui::Context) {
         let _ = ctx;
     }
 
+    fn ui_frame(&mut self, ctx: &egui::Context, frame: &FrameContext) {
+        let _ = frame;
+        self.ui_draw(ctx);
+    }
+
     fn handle_event(&mut self, event: winit::event::WindowEvent, state: &WgpuState) {
         let _ = event;
         let _ = state;
@@ -31,4 +61,21 @@ pub trait Render {
         let _ = encoder;
         Ok(())
     }
+
+    // 以固定步长调用（默认 60 Hz），与渲染帧率无关；渲染时可用 FrameContext::alpha 插值。
+    // 只在渲染帧中执行，RedrawMode::Reactive 下需要 continuous_redraw 返回 true 才能持续更新
+    fn tick(&mut self, dt: Duration, state: &WgpuState) {
+        let _ = dt;
+        let _ = state;
+    }
+
+    // 返回 true 时即使处于响应式重绘模式也会每帧重绘，用于动画内容
+    fn continuous_redraw(&self) -> bool {
+        false
+    }
+
+    // 默认转发给旧的 render 接口，新代码建议直接实现此方法
+    fn render_frame(&self, frame: &mut FrameContext) -> anyhow::Result<()> {
+        self.render(frame.state, frame.view, &mut frame.encoder)
+    }
 }
@@ -0,0 +1,176 @@
+use crate::{Navigator, PageInfo, Pages, Route};
+use egui::{Key, KeyboardShortcut, Modifiers};
+
+type CustomNavigation = Box<dyn FnMut(&egui::Context, &Pages, &Navigator)>;
+
+// 页面导航界面，通过 AppBuilder::navigation 或 Pages::set_navigation 设置
+#[derive(Default)]
+pub enum NavigationUi {
+    // 不显示导航，只能通过 Navigator 或快捷键切换页面
+    None,
+    // 带下拉框的 "Select Page" 窗口
+    #[default]
+    Window,
+    // 左侧面板，按分类分组并支持搜索
+    SidePanel,
+    // 顶部标签页
+    Tabs,
+    // 自定义界面，通过 Navigator 切换页面
+    Custom(CustomNavigation),
+}
+
+impl NavigationUi {
+    pub fn custom(f: impl FnMut(&egui::Context, &Pages, &Navigator) + 'static) -> Self {
+        Self::Custom(Box::new(f))
+    }
+
+    pub(crate) fn show(&mut self, ctx: &egui::Context, pages: &Pages, navigator: &Navigator) {
+        match self {
+            NavigationUi::None => {}
+            NavigationUi::Window => show_window(ctx, pages, navigator),
+            NavigationUi::SidePanel => show_side_panel(ctx, pages, navigator),
+            NavigationUi::Tabs => show_tabs(ctx, pages, navigator),
+            NavigationUi::Custom(f) => f(ctx, pages, navigator),
+        }
+    }
+}
+
+// 导航快捷键，设为 None 可以禁用
+#[derive(Clone, Copy, Debug)]
+pub struct NavigationShortcuts {
+    pub next: Option<KeyboardShortcut>,
+    pub previous: Option<KeyboardShortcut>,
+    // 显示或隐藏导航界面
+    pub toggle: Option<KeyboardShortcut>,
+}
+
+impl Default for NavigationShortcuts {
+    fn default() -> Self {
+        Self {
+            next: Some(KeyboardShortcut::new(Modifiers::CTRL, Key::Tab)),
+            previous: Some(KeyboardShortcut::new(
+                Modifiers::CTRL | Modifiers::SHIFT,
+                Key::Tab,
+            )),
+            toggle: Some(KeyboardShortcut::new(Modifiers::CTRL, Key::H)),
+        }
+    }
+}
+
+impl NavigationShortcuts {
+    pub fn none() -> Self {
+        Self {
+            next: None,
+            previous: None,
+            toggle: None,
+        }
+    }
+
+    pub(crate) fn handle(&self, ctx: &egui::Context, pages: &Pages, navigator: &Navigator) {
+        let consume = |shortcut: Option<KeyboardShortcut>| {
+            shortcut
+                .is_some_and(|shortcut| ctx.input_mut(|input| input.consume_shortcut(&shortcut)))
+        };
+        // 先检查带 Shift 的组合，避免被 Ctrl+Tab 吃掉
+        if consume(self.previous) {
+            cycle(pages, navigator, -1);
+        } else if consume(self.next) {
+            cycle(pages, navigator, 1);
+        }
+        if consume(self.toggle) {
+            navigator.set_navigation_visible(!navigator.navigation_visible());
+        }
+    }
+}
+
+// 按显示顺序循环切换，不记录历史
+fn cycle(pages: &Pages, navigator: &Navigator, step: isize) {
+    let infos: Vec<&PageInfo> = pages.infos().collect();
+    if infos.is_empty() {
+        return;
+    }
+    let index = infos
+        .iter()
+        .position(|info| info.id == pages.current)
+        .unwrap_or(0) as isize;
+    let next = (index + step).rem_euclid(infos.len() as isize) as usize;
+    navigator.replace_route(Route::new(infos[next].id.clone()));
+}
+
+fn show_window(ctx: &egui::Context, pages: &Pages, navigator: &Navigator) {
+    egui::Window::new("Select Page").show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Page");
+            let title = pages
+                .info(&pages.current)
+                .map(|info| info.title.as_str())
+                .unwrap_or_default();
+            egui::ComboBox::from_id_salt("page")
+                .selected_text(title)
+                .show_ui(ui, |ui| {
+                    for info in pages.infos() {
+                        page_button(ui, pages, navigator, info);
+                    }
+                });
+        })
+    });
+}
+
+fn show_side_panel(ctx: &egui::Context, pages: &Pages, navigator: &Navigator) {
+    egui::SidePanel::left("pages").show(ctx, |ui| {
+        // 搜索内容保存在 egui 的 Memory 中
+        let id = ui.id().with("search");
+        let mut search: String = ui.data_mut(|data| data.get_temp(id).unwrap_or_default());
+        ui.add(egui::TextEdit::singleline(&mut search).hint_text("Search"));
+        ui.data_mut(|data| data.insert_temp(id, search.clone()));
+        ui.separator();
+
+        let search = search.to_lowercase();
+        let matched: Vec<&PageInfo> = pages
+            .infos()
+            .filter(|info| search.is_empty() || info.title.to_lowercase().contains(&search))
+            .collect();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            // 没有分类的页面放在最前面，分类按第一次出现的顺序排列
+            for info in matched.iter().filter(|info| info.category.is_none()) {
+                page_button(ui, pages, navigator, info);
+            }
+            let mut categories: Vec<&str> = Vec::new();
+            for category in matched.iter().filter_map(|info| info.category.as_deref()) {
+                if !categories.contains(&category) {
+                    categories.push(category);
+                }
+            }
+            for category in categories {
+                egui::CollapsingHeader::new(category)
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        for info in matched
+                            .iter()
+                            .filter(|info| info.category.as_deref() == Some(category))
+                        {
+                            page_button(ui, pages, navigator, info);
+                        }
+                    });
+            }
+        });
+    });
+}
+
+fn show_tabs(ctx: &egui::Context, pages: &Pages, navigator: &Navigator) {
+    egui::TopBottomPanel::top("pages").show(ctx, |ui| {
+        ui.horizontal_wrapped(|ui| {
+            for info in pages.infos() {
+                page_button(ui, pages, navigator, info);
+            }
+        });
+    });
+}
+
+fn page_button(ui: &mut egui::Ui, pages: &Pages, navigator: &Navigator, info: &PageInfo) {
+    let selected = info.id == pages.current;
+    if ui.selectable_label(selected, &info.title).clicked() && !selected {
+        navigator.push_route(Route::new(info.id.clone()));
+    }
+}
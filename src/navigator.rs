@@ -0,0 +1,106 @@
+use crate::{Page, PageInfo, RepaintHandle, Route};
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Navigation {
+    // 切换到页面，当前页面进入历史记录
+    Push(Route),
+    // 切换到页面，不记录历史
+    Replace(Route),
+    Back,
+}
+
+#[derive(Default)]
+struct Inner {
+    requests: Vec<Navigation>,
+    history_len: usize,
+    navigation_hidden: bool,
+    repaint: Option<RepaintHandle>,
+}
+
+// 页面切换句柄，每个窗口一个，通过 WgpuState::navigator 获取。
+// 请求在下一帧开始时由 Pages 处理，可以在 update、ui_draw 或后台任务中调用
+#[derive(Clone, Default)]
+pub struct Navigator {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl Navigator {
+    // route 为页面 ID，可以带参数，例如 `scene?file=foo.gltf`
+    pub fn push(&self, route: &str) {
+        if let Some(route) = parse(route) {
+            self.push_route(route);
+        }
+    }
+
+    pub fn push_route(&self, route: Route) {
+        self.request(Navigation::Push(route));
+    }
+
+    pub fn push_page<T: Page>(&self) {
+        self.push_route(Route::new(PageInfo::of::<T>().id));
+    }
+
+    pub fn replace(&self, route: &str) {
+        if let Some(route) = parse(route) {
+            self.replace_route(route);
+        }
+    }
+
+    pub fn replace_route(&self, route: Route) {
+        self.request(Navigation::Replace(route));
+    }
+
+    pub fn replace_page<T: Page>(&self) {
+        self.replace_route(Route::new(PageInfo::of::<T>().id));
+    }
+
+    // 返回上一个 push 之前的页面，没有历史记录时忽略
+    pub fn back(&self) {
+        self.request(Navigation::Back);
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        self.inner.lock().unwrap().history_len > 0
+    }
+
+    fn request(&self, navigation: Navigation) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.requests.push(navigation);
+        if let Some(repaint) = &inner.repaint {
+            repaint.request_repaint();
+        }
+    }
+
+    // 显示或隐藏页面导航界面
+    pub fn set_navigation_visible(&self, visible: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.navigation_hidden = !visible;
+        if let Some(repaint) = &inner.repaint {
+            repaint.request_repaint();
+        }
+    }
+
+    pub fn navigation_visible(&self) -> bool {
+        !self.inner.lock().unwrap().navigation_hidden
+    }
+
+    pub(crate) fn take_requests(&self) -> Vec<Navigation> {
+        std::mem::take(&mut self.inner.lock().unwrap().requests)
+    }
+
+    pub(crate) fn set_history_len(&self, len: usize) {
+        self.inner.lock().unwrap().history_len = len;
+    }
+
+    pub(crate) fn set_repaint_handle(&self, handle: RepaintHandle) {
+        self.inner.lock().unwrap().repaint = Some(handle);
+    }
+}
+
+fn parse(route: &str) -> Option<Route> {
+    route
+        .parse()
+        .inspect_err(|err| tracing::warn!("Invalid route {:?}: {}", route, err))
+        .ok()
+}
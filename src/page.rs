@@ -1,128 +1,803 @@
-use crate::{Render, WgpuState};
+use crate::{
+    FrameContext, Render, RepaintHandle, WgpuState,
+    command::{Command, CommandQueue},
+    navigation::{NavigationShortcuts, NavigationUi},
+    navigator::Navigation,
+    route::{Params, Route},
+    subscription::{Subscription, SubscriptionTracker},
+};
 use std::{
+    any::Any,
     collections::HashMap,
-    sync::{Arc, Mutex, RwLock},
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+    time::Duration,
+};
+use tokio::{
+    runtime::Handle,
+    sync::mpsc::{Receiver, Sender, channel},
 };
-use tokio::sync::mpsc::{Sender, channel};
+use tokio_util::sync::CancellationToken;
+use winit::dpi::PhysicalSize;
+
+type PendingTasks = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+// 每个页面消息队列的容量，消息在每帧开始时统一处理
+const MESSAGE_CAPACITY: usize = 64;
+
+// 页面的构建和卸载方式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PagePolicy {
+    // 窗口创建时构建，之后一直保留
+    #[default]
+    Eager,
+    // 第一次进入时构建，之后一直保留
+    Lazy,
+    // 进入时构建，离开时销毁以释放 GPU 资源，可以通过 snapshot/restore 保留状态
+    UnloadOnLeave,
+}
 
 pub trait Page: Render {
-    type Message;
+    type Message: Send + 'static;
+
+    // 页面的唯一标识，为空时使用完整的类型路径
+    const ID: &'static str = "";
+    // 页面导航中显示的名称，为空时使用类型名
+    const TITLE: &'static str = "";
+    const CATEGORY: Option<&'static str> = None;
+    // 页面导航中按 ORDER 从小到大排列，相同时按注册顺序
+    const ORDER: i32 = 0;
+    const POLICY: PagePolicy = PagePolicy::Eager;
 
-    fn new(state: &WgpuState, sender: Sender<Self::Message>) -> Self
+    // cancel 在页面被销毁、窗口关闭或应用退出时取消，页面自己启动的后台任务应该监听它
+    fn new(state: &WgpuState, sender: Sender<Self::Message>, cancel: CancellationToken) -> Self
     where
         Self: Sized;
 
-    fn update(&mut self, message: Self::Message, state: &WgpuState) {
+    // 在事件循环线程上、每帧绘制 UI 之前调用，返回的命令在 tokio 运行时上执行
+    fn update(&mut self, message: Self::Message, state: &WgpuState) -> Command<Self::Message> {
         let _ = message;
         let _ = state;
+        Command::none()
+    }
+
+    // 当前页面的长期输入源，每帧比较一次，切换到其他页面时全部停止
+    fn subscriptions(&self) -> Subscription<Self::Message> {
+        Subscription::none()
+    }
+
+    // 命令执行失败，或者出现 GPU 校验错误（GpuError::Validation）时调用
+    fn on_error(&mut self, error: anyhow::Error, state: &WgpuState) {
+        let _ = state;
+        tracing::error!("Page error: {:#}", error);
+    }
+
+    // 成为当前页面时调用（包括第一帧）
+    fn on_enter(&mut self, params: &Params, state: &WgpuState) {
+        let _ = params;
+        let _ = state;
+    }
+
+    // 切换到其他页面时调用
+    fn on_leave(&mut self, state: &WgpuState) {
+        let _ = state;
+    }
+
+    // 当前页面立即收到；其他页面在下一次 on_enter 之前收到最后一次尺寸
+    fn on_resize(&mut self, size: PhysicalSize<u32>, state: &WgpuState) {
+        let _ = size;
+        let _ = state;
+    }
+
+    // 应用被挂起/恢复时所有页面都会收到
+    fn on_suspend(&mut self, state: &WgpuState) {
+        let _ = state;
+    }
+
+    fn on_resume(&mut self, state: &WgpuState) {
+        let _ = state;
+    }
+
+    // 用户关闭窗口时调用，返回 false 可以阻止关闭（例如有未保存的修改），
+    // 之后可以通过 WgpuState::close_window 强制关闭
+    fn on_close_requested(&mut self, state: &WgpuState) -> bool {
+        let _ = state;
+        true
+    }
+
+    // 窗口关闭或应用退出、页面被销毁之前调用，此时 Device 仍然可用
+    fn teardown(&mut self, state: &WgpuState) {
+        let _ = state;
+    }
+
+    // UnloadOnLeave 页面卸载前保存状态，重新构建后交给 restore
+    fn snapshot(&self) -> Option<Box<dyn Any + Send>> {
+        None
+    }
+
+    fn restore(&mut self, snapshot: Box<dyn Any + Send>, state: &WgpuState) {
+        let _ = snapshot;
+        let _ = state;
+    }
+}
+
+// 页面及其消息队列，消息由事件循环线程取出后交给 Page::update
+pub(crate) trait PageHost {
+    fn page(&self) -> &dyn Render;
+
+    fn page_mut(&mut self) -> &mut dyn Render;
+
+    // 返回是否处理了消息，active 表示是否为当前页面
+    fn process_messages(&mut self, state: &WgpuState, waker: &Waker, active: bool) -> bool;
+
+    fn enter(&mut self, params: &Params, state: &WgpuState);
+
+    fn leave(&mut self, state: &WgpuState);
+
+    fn resize(&mut self, size: PhysicalSize<u32>, state: &WgpuState, active: bool);
+
+    fn error(&mut self, error: anyhow::Error, state: &WgpuState);
+
+    fn suspend(&mut self, state: &WgpuState);
+
+    fn resume(&mut self, state: &WgpuState);
+
+    fn close_requested(&mut self, state: &WgpuState) -> bool;
+
+    // 返回等待未完成命令结束的 future，drop 时取消这些命令
+    fn teardown(&mut self, state: &WgpuState) -> PendingTasks;
+
+    fn snapshot(&self) -> Option<Box<dyn Any + Send>>;
+
+    fn restore(&mut self, snapshot: Box<dyn Any + Send>, state: &WgpuState);
+
+    // 以下供 PageHarness 取出具体的页面类型和消息发送端
+    fn as_any(&self) -> &dyn Any;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    fn sender(&self) -> Box<dyn Any>;
+
+    fn has_pending_commands(&self) -> bool;
+}
+
+struct PageSlot<T: Page> {
+    page: T,
+    sender: Sender<T::Message>,
+    receiver: Receiver<T::Message>,
+    commands: CommandQueue<T::Message>,
+    subscriptions: SubscriptionTracker,
+    handle: Handle,
+    cancel: CancellationToken,
+    // 页面不可见期间收到的最后一次尺寸变化
+    pending_resize: Option<PhysicalSize<u32>>,
+}
+
+impl<T: Page + 'static> PageHost for PageSlot<T> {
+    fn page(&self) -> &dyn Render {
+        &self.page
+    }
+
+    fn page_mut(&mut self) -> &mut dyn Render {
+        &mut self.page
+    }
+
+    fn process_messages(&mut self, state: &WgpuState, waker: &Waker, active: bool) -> bool {
+        let mut cx = Context::from_waker(waker);
+        let mut processed = false;
+        // 队列为空时注册 waker，后台任务发送消息或命令完成后会唤醒事件循环
+        loop {
+            let result = match self.receiver.poll_recv(&mut cx) {
+                Poll::Ready(Some(message)) => Ok(message),
+                _ => match self.commands.poll_next(&mut cx) {
+                    Poll::Ready(Some(result)) => result,
+                    _ => break,
+                },
+            };
+            match result {
+                Ok(message) => {
+                    let command = self.page.update(message, state);
+                    self.commands.spawn(command);
+                }
+                Err(err) => self.page.on_error(err, state),
+            }
+            processed = true;
+        }
+
+        if active {
+            self.subscriptions
+                .update(self.page.subscriptions(), &self.sender, &self.handle);
+        } else {
+            self.subscriptions.stop();
+        }
+        processed
+    }
+
+    fn enter(&mut self, params: &Params, state: &WgpuState) {
+        if let Some(size) = self.pending_resize.take() {
+            self.page.on_resize(size, state);
+        }
+        self.page.on_enter(params, state);
+    }
+
+    fn leave(&mut self, state: &WgpuState) {
+        self.page.on_leave(state);
+    }
+
+    fn resize(&mut self, size: PhysicalSize<u32>, state: &WgpuState, active: bool) {
+        if active {
+            self.page.on_resize(size, state);
+        } else {
+            self.pending_resize = Some(size);
+        }
+    }
+
+    fn error(&mut self, error: anyhow::Error, state: &WgpuState) {
+        self.page.on_error(error, state);
+    }
+
+    fn suspend(&mut self, state: &WgpuState) {
+        self.page.on_suspend(state);
+    }
+
+    fn resume(&mut self, state: &WgpuState) {
+        self.page.on_resume(state);
+    }
+
+    fn close_requested(&mut self, state: &WgpuState) -> bool {
+        self.page.on_close_requested(state)
+    }
+
+    fn teardown(&mut self, state: &WgpuState) -> PendingTasks {
+        self.cancel.cancel();
+        self.subscriptions.stop();
+        self.page.teardown(state);
+        let commands =
+            std::mem::replace(&mut self.commands, CommandQueue::new(self.handle.clone()));
+        Box::pin(commands.finish())
+    }
+
+    fn snapshot(&self) -> Option<Box<dyn Any + Send>> {
+        self.page.snapshot()
+    }
+
+    fn restore(&mut self, snapshot: Box<dyn Any + Send>, state: &WgpuState) {
+        self.page.restore(snapshot, state);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        &self.page
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        &mut self.page
+    }
+
+    fn sender(&self) -> Box<dyn Any> {
+        Box::new(self.sender.clone())
+    }
+
+    fn has_pending_commands(&self) -> bool {
+        !self.commands.is_empty()
     }
 }
 
-fn create_component<T, M>(state: Arc<Mutex<Option<WgpuState>>>) -> Arc<RwLock<dyn Render>>
+fn create_component<T, M>(state: &WgpuState, handle: &Handle) -> Box<dyn PageHost>
 where
-    T: Page<Message = M> + Send + Sync + 'static,
-    M: Send + Sync + 'static,
+    T: Page<Message = M> + 'static,
+    M: Send + 'static,
 {
-    let (sender, mut receiver) = channel(1);
-    let state_ref = state.clone();
-    let state_ref = state_ref.lock().unwrap();
-    let state_ref = state_ref.as_ref().expect("WgpuState is not initialized");
-
-    let component = Arc::new(RwLock::new(T::new(state_ref, sender)));
-
-    tokio::spawn({
-        let component = component.clone();
-        async move {
-            while let Some(message) = receiver.recv().await {
-                let state_ref = state.clone();
-                let state_ref = state_ref.lock().unwrap();
-                let state_ref = state_ref.as_ref().expect("WgpuState is not initialized");
-                component.write().unwrap().update(message, state_ref);
-            }
+    let (sender, receiver) = channel(MESSAGE_CAPACITY);
+    let cancel = state.cancellation.child_token();
+    let page = T::new(state, sender.clone(), cancel.clone());
+    Box::new(PageSlot {
+        page,
+        sender,
+        receiver,
+        commands: CommandQueue::new(handle.clone()),
+        subscriptions: SubscriptionTracker::new(),
+        handle: handle.clone(),
+        cancel,
+        pending_resize: None,
+    })
+}
+
+// 收到页面消息时请求重绘对应窗口
+struct RepaintWaker(RepaintHandle);
+
+impl Wake for RepaintWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.request_repaint();
+    }
+}
+
+type Register = Box<dyn Fn(&WgpuState, &Handle) -> Box<dyn PageHost>>;
+
+#[derive(Clone, Debug)]
+pub struct PageInfo {
+    pub id: String,
+    pub title: String,
+    pub category: Option<String>,
+    pub order: i32,
+}
+
+impl PageInfo {
+    pub(crate) fn of<T: Page>() -> Self {
+        let type_name = std::any::type_name::<T>();
+        let id = match T::ID {
+            "" => type_name,
+            id => id,
+        };
+        let title = match T::TITLE {
+            "" => type_name.split("::").last().unwrap(),
+            title => title,
+        };
+        Self {
+            id: id.to_string(),
+            title: title.to_string(),
+            category: T::CATEGORY.map(str::to_string),
+            order: T::ORDER,
         }
-    });
+    }
+}
 
-    component
+pub(crate) struct Registration {
+    info: PageInfo,
+    create: Register,
+    policy: PagePolicy,
+    // UnloadOnLeave 页面卸载时保存的状态
+    snapshot: Option<Box<dyn Any + Send>>,
 }
 
 pub struct Pages {
     pub current: String,
-    pub pages: HashMap<String, Arc<RwLock<dyn Render>>>,
-    pub registers: HashMap<
-        String,
-        Box<dyn Fn(Arc<Mutex<Option<WgpuState>>>) -> Arc<RwLock<dyn Render>> + Send + Sync>,
-    >,
+    // 当前页面的路由参数
+    pub params: Params,
+    // 已经构建的页面
+    pub(crate) pages: HashMap<String, Box<dyn PageHost>>,
+    // 按 ORDER 排序的注册信息
+    pub(crate) registers: Vec<Registration>,
+    handle: Option<Handle>,
+    waker: Waker,
+    // 最近一次收到 on_enter 的页面
+    entered: Option<Route>,
+    // push 之前的页面，back 时依次返回
+    history: Vec<Route>,
+    // 由 start_on 或 start_at 指定的初始页面
+    initial: Option<Route>,
+    navigation: NavigationUi,
+    shortcuts: NavigationShortcuts,
 }
 
 impl Pages {
     pub(crate) fn new() -> Self {
         Self {
             current: "".to_string(),
+            params: Params::new(),
             pages: HashMap::new(),
-            registers: HashMap::new(),
+            registers: Vec::new(),
+            handle: None,
+            waker: Waker::noop().clone(),
+            entered: None,
+            history: Vec::new(),
+            initial: None,
+            navigation: NavigationUi::default(),
+            shortcuts: NavigationShortcuts::default(),
         }
     }
 
-    pub fn register<T, M>(&mut self)
+    // 同一个 ID 重复注册时返回错误
+    pub fn register<T, M>(&mut self) -> anyhow::Result<()>
     where
-        T: Page<Message = M> + Send + Sync + 'static,
-        M: Send + Sync + 'static,
+        T: Page<Message = M> + 'static,
+        M: Send + 'static,
     {
-        let name = std::any::type_name::<T>()
-            .split("::")
-            .last()
-            .unwrap()
-            .to_string();
-        // 默认为最后一个注册的页面
-        self.current = name.clone();
+        let info = PageInfo::of::<T>();
+        if self.registration(&info.id).is_some() {
+            anyhow::bail!(
+                "Page {} is already registered ({})",
+                info.id,
+                std::any::type_name::<T>()
+            );
+        }
+        let res =
+            Box::new(|state: &WgpuState, handle: &Handle| create_component::<T, M>(state, handle));
+        let index = self
+            .registers
+            .partition_point(|registration| registration.info.order <= info.order);
+        self.registers.insert(
+            index,
+            Registration {
+                info,
+                create: res,
+                policy: T::POLICY,
+                snapshot: None,
+            },
+        );
+        Ok(())
+    }
+
+    // 指定初始页面，未指定时使用排序后的第一个页面
+    pub fn start_on<T: Page>(&mut self) {
+        self.initial = Some(Route::new(PageInfo::of::<T>().id));
+    }
+
+    // 带参数的初始页面，例如从命令行读取的 `scene?file=foo.gltf`
+    pub fn start_at(&mut self, route: Route) {
+        self.initial = Some(route);
+    }
+
+    pub fn set_navigation(&mut self, navigation: NavigationUi) {
+        self.navigation = navigation;
+    }
+
+    pub fn set_navigation_shortcuts(&mut self, shortcuts: NavigationShortcuts) {
+        self.shortcuts = shortcuts;
+    }
+
+    fn registration(&self, id: &str) -> Option<&Registration> {
+        self.registers
+            .iter()
+            .find(|registration| registration.info.id == id)
+    }
+
+    // 按显示顺序返回所有已注册的页面
+    pub fn infos(&self) -> impl Iterator<Item = &PageInfo> {
+        self.registers.iter().map(|registration| &registration.info)
+    }
+
+    pub fn info(&self, id: &str) -> Option<&PageInfo> {
+        self.registration(id).map(|registration| &registration.info)
+    }
+
+    // 只构建 Eager 页面，其他页面在进入时构建
+    pub(crate) fn create(&mut self, state: &WgpuState, handle: &Handle) {
+        if let Some(repaint) = state.repaint_handle() {
+            self.waker = Waker::from(Arc::new(RepaintWaker(repaint)));
+        }
+        self.handle = Some(handle.clone());
+        if let Some(initial) = self.initial.take() {
+            if self.registration(&initial.id).is_some() {
+                self.set_route(initial);
+            } else {
+                tracing::warn!("Initial page {} is not registered", initial.id);
+            }
+        }
+        if self.current.is_empty()
+            && let Some(registration) = self.registers.first()
+        {
+            self.current = registration.info.id.clone();
+        }
+        for registration in self.registers.iter() {
+            if registration.policy == PagePolicy::Eager {
+                let component = (registration.create)(state, handle);
+                self.pages.insert(registration.info.id.clone(), component);
+            }
+        }
+    }
+
+    pub fn is_loaded(&self, name: &str) -> bool {
+        self.pages.contains_key(name)
+    }
+
+    // 页面未构建时通过注册函数重新构建，并恢复卸载时保存的状态
+    fn load(&mut self, name: &str, state: &WgpuState) -> Option<&mut Box<dyn PageHost>> {
+        if !self.pages.contains_key(name) {
+            let handle = self.handle.as_ref()?;
+            let registration = self
+                .registers
+                .iter_mut()
+                .find(|registration| registration.info.id == name)?;
+            let mut component = (registration.create)(state, handle);
+            if let Some(snapshot) = registration.snapshot.take() {
+                component.restore(snapshot, state);
+            }
+            tracing::info!("Loaded page {}", name);
+            self.pages.insert(name.to_string(), component);
+        }
+        self.pages.get_mut(name)
+    }
+
+    fn unload(&mut self, name: &str, state: &WgpuState) {
+        let Some(mut page) = self.pages.remove(name) else {
+            return;
+        };
+        let snapshot = page.snapshot();
+        // 卸载不等待未完成的命令，直接取消
+        drop(page.teardown(state));
+        if let Some(registration) = self
+            .registers
+            .iter_mut()
+            .find(|registration| registration.info.id == name)
+        {
+            registration.snapshot = snapshot;
+        }
+        tracing::info!("Unloaded page {}", name);
+    }
+
+    // 处理所有页面收到的消息并同步订阅，App 在每帧渲染前调用
+    pub fn process_messages(&mut self, state: &WgpuState) -> bool {
+        self.sync_current(state);
+        let mut processed = false;
+        for (name, page) in self.pages.iter_mut() {
+            processed |= page.process_messages(state, &self.waker, *name == self.current);
+        }
+        processed
+    }
+
+    pub(crate) fn navigate(&mut self, navigation: Navigation) {
+        match navigation {
+            Navigation::Push(route) | Navigation::Replace(route)
+                if self.registration(&route.id).is_none() =>
+            {
+                tracing::warn!("Cannot navigate to unknown page {}", route.id);
+            }
+            Navigation::Push(route) => {
+                if route != self.current_route() {
+                    let previous = self.set_route(route);
+                    self.history.push(previous);
+                }
+            }
+            Navigation::Replace(route) => {
+                self.set_route(route);
+            }
+            Navigation::Back => {
+                if let Some(previous) = self.history.pop() {
+                    self.set_route(previous);
+                }
+            }
+        }
+    }
+
+    // 返回之前的路由
+    fn set_route(&mut self, route: Route) -> Route {
+        let previous = self.current_route();
+        self.current = route.id;
+        self.params = route.params;
+        previous
+    }
+
+    pub fn current_route(&self) -> Route {
+        Route {
+            id: self.current.clone(),
+            params: self.params.clone(),
+        }
+    }
+
+    pub fn history(&self) -> &[Route] {
+        &self.history
+    }
+
+    // 处理 Navigator 的请求，然后通知离开和进入的页面
+    pub(crate) fn sync_current(&mut self, state: &WgpuState) {
+        for navigation in state.navigator.take_requests() {
+            self.navigate(navigation);
+        }
+        state.navigator.set_history_len(self.history.len());
+
+        // 同一个页面参数变化时也会重新进入
+        let current = self.current_route();
+        if self.entered.as_ref() == Some(&current) {
+            return;
+        }
+        if let Some(Route { id: name, .. }) = self.entered.take() {
+            if let Some(page) = self.pages.get_mut(&name) {
+                page.leave(state);
+            }
+            // 只有参数变化时保留页面实例，不卸载也不重新构建
+            if name != current.id
+                && self
+                    .registration(&name)
+                    .is_some_and(|registration| registration.policy == PagePolicy::UnloadOnLeave)
+            {
+                self.unload(&name, state);
+            }
+        }
+        if let Some(page) = self.load(&current.id, state) {
+            page.enter(&current.params, state);
+            self.entered = Some(current);
+        }
+    }
+
+    pub fn resize(&mut self, size: PhysicalSize<u32>, state: &WgpuState) {
+        for (name, page) in self.pages.iter_mut() {
+            let active = self.entered.as_ref().is_some_and(|route| route.id == *name);
+            page.resize(size, state, active);
+        }
+    }
+
+    // 把不属于某个命令的错误（例如 GPU 校验错误）交给当前页面
+    pub(crate) fn report_error(&mut self, error: anyhow::Error, state: &WgpuState) {
+        match self.pages.get_mut(&self.current) {
+            Some(page) => page.error(error, state),
+            None => tracing::error!("{:#}", error),
+        }
+    }
+
+    pub fn suspend(&mut self, state: &WgpuState) {
+        for page in self.pages.values_mut() {
+            page.suspend(state);
+        }
+    }
+
+    pub fn resume(&mut self, state: &WgpuState) {
+        for page in self.pages.values_mut() {
+            page.resume(state);
+        }
+    }
+
+    // 离开当前页面并销毁所有页面，未完成的命令直接取消
+    pub fn teardown(&mut self, state: &WgpuState) {
+        drop(self.shutdown(state));
+    }
+
+    // 与 teardown 相同，但返回等待所有页面未完成命令结束的 future
+    pub(crate) fn shutdown(&mut self, state: &WgpuState) -> PendingTasks {
+        if let Some(page) = self
+            .entered
+            .take()
+            .and_then(|route| self.pages.get_mut(&route.id))
+        {
+            page.leave(state);
+        }
+        let tasks: Vec<PendingTasks> = self
+            .pages
+            .drain()
+            .map(|(_, mut page)| page.teardown(state))
+            .collect();
+        Box::pin(async move {
+            for task in tasks {
+                task.await;
+            }
+        })
+    }
+
+    // 已经构建的 T 类型页面，供 PageHarness 使用
+    pub(crate) fn page<T: Page + 'static>(&self) -> Option<&T> {
+        self.pages
+            .get(&PageInfo::of::<T>().id)?
+            .as_any()
+            .downcast_ref()
+    }
+
+    pub(crate) fn page_mut<T: Page + 'static>(&mut self) -> Option<&mut T> {
+        self.pages
+            .get_mut(&PageInfo::of::<T>().id)?
+            .as_any_mut()
+            .downcast_mut()
+    }
+
+    pub(crate) fn sender<T: Page + 'static>(&self) -> Option<Sender<T::Message>> {
+        self.pages
+            .get(&PageInfo::of::<T>().id)?
+            .sender()
+            .downcast()
+            .ok()
+            .map(|sender| *sender)
+    }
 
-        let res = Box::new(|state| create_component::<T, M>(state));
-        self.registers.insert(name, res);
+    pub(crate) fn has_pending_commands(&self) -> bool {
+        self.pages.values().any(|page| page.has_pending_commands())
     }
 
-    pub(crate) fn create(&mut self, state: Arc<Mutex<Option<WgpuState>>>) {
-        for (name, register) in self.registers.iter() {
-            let component = register(state.clone());
-            self.pages.insert(name.clone(), component);
+    // 页面收到消息或命令完成时调用的 waker，App 中为请求重绘
+    pub(crate) fn set_waker(&mut self, waker: Waker) {
+        self.waker = waker;
+    }
+
+    // 模拟切换到一个空白页面，返回离开的路由；unload 为 true 时同时像 UnloadOnLeave 一样销毁页面。
+    // 之后没有当前页面，订阅停止，直到重新设置 current 并调用 sync_current
+    pub(crate) fn leave_current(&mut self, state: &WgpuState, unload: bool) -> Option<Route> {
+        let route = self.entered.take()?;
+        if let Some(page) = self.pages.get_mut(&route.id) {
+            page.leave(state);
         }
+        if unload {
+            self.unload(&route.id, state);
+        }
+        self.set_route(Route::new(String::new()));
+        Some(route)
+    }
+
+    // 询问所有已构建的页面是否允许关闭窗口，每个页面都会被询问
+    pub(crate) fn close_requested(&mut self, state: &WgpuState) -> bool {
+        let mut allow = true;
+        for page in self.pages.values_mut() {
+            allow &= page.close_requested(state);
+        }
+        allow
     }
 }
 
 impl Render for Pages {
-    fn ui_draw(&mut self, ctx: &egui::Context) {
-        egui::Window::new("Select Page").show(ctx, |ui| {
-            ui.horizontal(|ui| {
-                ui.label("Page");
-                egui::ComboBox::from_id_salt("page")
-                    .selected_text(&self.current)
-                    .show_ui(ui, |ui| {
-                        for (name, _) in self.pages.iter() {
-                            ui.selectable_value(&mut self.current, name.clone(), name);
-                        }
-                    })
-            })
-        });
+    fn ui_frame(&mut self, ctx: &egui::Context, frame: &FrameContext) {
+        let navigator = &frame.state.navigator;
+        self.shortcuts.handle(ctx, self, navigator);
+        if navigator.navigation_visible() {
+            // 导航界面只读取 Pages，切换请求通过 Navigator 发出
+            let mut navigation = std::mem::replace(&mut self.navigation, NavigationUi::None);
+            navigation.show(ctx, self, navigator);
+            self.navigation = navigation;
+        }
+        // 在绘制新页面之前完成切换
+        self.sync_current(frame.state);
 
         if let Some(page) = self.pages.get_mut(&self.current) {
-            page.write().unwrap().ui_draw(ctx);
+            page.page_mut().ui_frame(ctx, frame);
         }
     }
 
-    fn render(
-        &self,
-        state: &WgpuState,
-        view: &wgpu::TextureView,
-        encoder: &mut wgpu::CommandEncoder,
-    ) -> anyhow::Result<()> {
+    fn render_frame(&self, frame: &mut FrameContext) -> anyhow::Result<()> {
         if let Some(page) = self.pages.get(&self.current) {
-            page.read().unwrap().render(state, view, encoder)?;
+            page.page().render_frame(frame)?;
         }
         Ok(())
     }
 
     fn handle_event(&mut self, event: winit::event::WindowEvent, state: &WgpuState) {
         if let Some(page) = self.pages.get_mut(&self.current) {
-            page.write().unwrap().handle_event(event, state);
+            page.page_mut().handle_event(event, state);
+        }
+    }
+
+    fn continuous_redraw(&self) -> bool {
+        self.pages
+            .get(&self.current)
+            .is_some_and(|page| page.page().continuous_redraw())
+    }
+
+    fn tick(&mut self, dt: Duration, state: &WgpuState) {
+        if let Some(page) = self.pages.get_mut(&self.current) {
+            page.page_mut().tick(dt, state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static BUILT: AtomicUsize = AtomicUsize::new(0);
+    static ENTERED: AtomicUsize = AtomicUsize::new(0);
+
+    struct Scene;
+
+    impl Render for Scene {}
+
+    impl Page for Scene {
+        type Message = ();
+        const ID: &'static str = "scene";
+        const POLICY: PagePolicy = PagePolicy::UnloadOnLeave;
+
+        fn new(_state: &WgpuState, _sender: Sender<()>, _cancel: CancellationToken) -> Self {
+            BUILT.fetch_add(1, Ordering::SeqCst);
+            Scene
+        }
+
+        fn on_enter(&mut self, _params: &Params, _state: &WgpuState) {
+            ENTERED.fetch_add(1, Ordering::SeqCst);
         }
     }
+
+    #[test]
+    fn params_change_keeps_unload_on_leave_page() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let state = pollster::block_on(WgpuState::headless(64, 64)).unwrap();
+        let mut pages = Pages::new();
+        pages.register::<Scene, _>().unwrap();
+        pages.create(&state, runtime.handle());
+
+        pages.process_messages(&state);
+        assert_eq!(BUILT.load(Ordering::SeqCst), 1);
+        assert_eq!(ENTERED.load(Ordering::SeqCst), 1);
+
+        state.navigator.push("scene?file=foo.gltf");
+        pages.process_messages(&state);
+        assert_eq!(pages.current_route().params.raw("file"), Some("foo.gltf"));
+        assert_eq!(BUILT.load(Ordering::SeqCst), 1);
+        assert_eq!(ENTERED.load(Ordering::SeqCst), 2);
+    }
 }
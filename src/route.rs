@@ -0,0 +1,227 @@
+use anyhow::{anyhow, bail};
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+// 页面参数，值在取出时按需解析为具体类型
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Params(BTreeMap<String, String>);
+
+impl Params {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, key: impl Into<String>, value: impl ToString) -> Self {
+        self.insert(key, value);
+        self
+    }
+
+    pub fn insert(&mut self, key: impl Into<String>, value: impl ToString) {
+        self.0.insert(key.into(), value.to_string());
+    }
+
+    pub fn raw(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    // 参数不存在时返回 Ok(None)，解析失败时返回错误
+    pub fn get<T>(&self, key: &str) -> anyhow::Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        self.raw(key)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|err| anyhow!("Invalid page parameter {}={}: {}", key, value, err))
+            })
+            .transpose()
+    }
+
+    pub fn require<T>(&self, key: &str) -> anyhow::Result<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        self.get(key)?
+            .ok_or_else(|| anyhow!("Missing page parameter {}", key))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
+
+// 页面 ID 加参数，字符串形式为 `scene?file=foo.gltf&camera=top`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Route {
+    pub id: String,
+    pub params: Params,
+}
+
+impl Route {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            params: Params::new(),
+        }
+    }
+
+    pub fn with(mut self, key: impl Into<String>, value: impl ToString) -> Self {
+        self.params.insert(key, value);
+        self
+    }
+
+    // 从命令行参数中读取 `--page <route>` 或 `--page=<route>`
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> anyhow::Result<Option<Self>> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--page" {
+                let Some(route) = args.next() else {
+                    bail!("Missing value for --page");
+                };
+                return route.parse().map(Some);
+            }
+            if let Some(route) = arg.strip_prefix("--page=") {
+                return route.parse().map(Some);
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl FromStr for Route {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (id, query) = s.split_once('?').unwrap_or((s, ""));
+        let id = decode(id)?;
+        if id.is_empty() {
+            bail!("Missing page id in route {:?}", s);
+        }
+        let mut params = Params::new();
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            params.insert(decode(key)?, decode(value)?);
+        }
+        Ok(Self { id, params })
+    }
+}
+
+impl fmt::Display for Route {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", encode(&self.id))?;
+        for (index, (key, value)) in self.params.iter().enumerate() {
+            let separator = if index == 0 { '?' } else { '&' };
+            write!(f, "{}{}={}", separator, encode(key), encode(value))?;
+        }
+        Ok(())
+    }
+}
+
+fn encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'%' | b'?' | b'&' | b'=' | b'+' | b'#' | b' ' | 0..0x20 | 0x7f.. => {
+                encoded.push_str(&format!("%{:02X}", byte));
+            }
+            _ => encoded.push(byte as char),
+        }
+    }
+    encoded
+}
+
+// 解码 `%XX` 和 `+`
+fn decode(s: &str) -> anyhow::Result<String> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut iter = s.bytes();
+    while let Some(byte) = iter.next() {
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = [iter.next(), iter.next()];
+                let value = match hex {
+                    [Some(h), Some(l)] => std::str::from_utf8(&[h, l])
+                        .ok()
+                        .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+                    _ => None,
+                };
+                bytes.push(value.ok_or_else(|| anyhow!("Invalid escape in {:?}", s))?);
+            }
+            _ => bytes.push(byte),
+        }
+    }
+    Ok(String::from_utf8(bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_id_and_params() {
+        let route: Route = "scene?file=foo.gltf&camera=top&flag".parse().unwrap();
+        assert_eq!(route.id, "scene");
+        assert_eq!(route.params.raw("file"), Some("foo.gltf"));
+        assert_eq!(route.params.raw("camera"), Some("top"));
+        assert_eq!(route.params.raw("flag"), Some(""));
+    }
+
+    #[test]
+    fn decodes_escapes_and_plus() {
+        let route: Route = "my%20page?q=a+b%26c&x%3Dy=%25".parse().unwrap();
+        assert_eq!(route.id, "my page");
+        assert_eq!(route.params.raw("q"), Some("a b&c"));
+        assert_eq!(route.params.raw("x=y"), Some("%"));
+    }
+
+    #[test]
+    fn rejects_invalid_routes() {
+        assert!("".parse::<Route>().is_err());
+        assert!("?a=1".parse::<Route>().is_err());
+        assert!("page?a=%2".parse::<Route>().is_err());
+        assert!("page?a=%zz".parse::<Route>().is_err());
+        assert!("page?a=%ff".parse::<Route>().is_err());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let route = Route::new("image texture")
+            .with("url", "https://example.com/a.png?x=1&y=2")
+            .with("scale", 1.5)
+            .with("note", "50% + more #1");
+        let text = route.to_string();
+        assert!(!text.contains(' '));
+        assert_eq!(text.parse::<Route>().unwrap(), route);
+        assert_eq!(Route::new("plain").to_string(), "plain");
+    }
+
+    #[test]
+    fn typed_params() {
+        let params = Params::new().with("count", 3).with("name", "x");
+        assert_eq!(params.get::<u32>("count").unwrap(), Some(3));
+        assert_eq!(params.get::<u32>("missing").unwrap(), None);
+        assert!(params.get::<u32>("name").is_err());
+        assert!(params.require::<u32>("missing").is_err());
+    }
+
+    #[test]
+    fn reads_page_from_args() {
+        let route = Route::from_args(args(&["app", "--page", "scene?file=a"])).unwrap();
+        assert_eq!(route, Some(Route::new("scene").with("file", "a")));
+        let route = Route::from_args(args(&["app", "-v", "--page=triangle"])).unwrap();
+        assert_eq!(route, Some(Route::new("triangle")));
+        assert_eq!(Route::from_args(args(&["app"])).unwrap(), None);
+        assert!(Route::from_args(args(&["app", "--page"])).is_err());
+        assert!(Route::from_args(args(&["app", "--page="])).is_err());
+    }
+}
@@ -0,0 +1,356 @@
+use crate::{RepaintHandle, WgpuState};
+use anyhow::anyhow;
+use naga::{
+    SourceLocation,
+    valid::{Capabilities, ValidationFlags, Validator},
+};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, Weak},
+};
+use wgpu::{ErrorFilter, ShaderModule, ShaderModuleDescriptor, ShaderSource};
+
+// WgpuState 记录本窗口加载的着色器，用于显示编译错误
+pub(crate) type ShaderRegistry = Mutex<Vec<Weak<Mutex<ShaderInner>>>>;
+
+// 着色器编译错误，line 和 column 从 1 开始
+#[derive(Clone, Debug)]
+pub struct ShaderError {
+    pub path: PathBuf,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub message: String,
+    // naga 生成的完整报告，包含出错位置的代码片段
+    pub report: String,
+}
+
+impl ShaderError {
+    fn new(
+        path: &Path,
+        location: Option<SourceLocation>,
+        message: impl Into<String>,
+        report: String,
+    ) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            line: location.map(|location| location.line_number),
+            column: location.map(|location| location.line_position),
+            message: message.into(),
+            report,
+        }
+    }
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, ":{}:{}", line, column)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for ShaderError {}
+
+pub(crate) struct ShaderInner {
+    path: PathBuf,
+    // 最近一次读取到的源码，用于忽略内容没有变化的文件事件
+    source: String,
+    // 最近一次编译成功的模块，文件第一次加载就无法编译时为 None
+    module: Option<ShaderModule>,
+    version: u64,
+    // 已经通过 naga 校验、等待在事件循环线程上创建模块的源码
+    pending: Option<String>,
+    error: Option<ShaderError>,
+}
+
+// 从磁盘加载的 WGSL 着色器，文件保存后自动重新加载。
+// 新的源码先在后台用 naga 校验，通过后才替换模块，失败时继续使用上一次成功的模块；
+// 第一次加载就无法编译时没有可用的模块，错误显示在 "Shader Errors" 窗口中，直到文件修复
+#[derive(Clone)]
+pub struct ShaderAsset {
+    inner: Arc<Mutex<ShaderInner>>,
+    _watcher: Arc<RecommendedWatcher>,
+}
+
+impl ShaderAsset {
+    // 只有文件无法读取或监听时返回错误，编译错误不会导致加载失败
+    pub fn load(state: &WgpuState, path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let source = std::fs::read_to_string(&path)
+            .map_err(|err| anyhow!("Failed to read shader {}: {}", path.display(), err))?;
+        let (module, error) =
+            match validate(&path, &source).and_then(|()| create_module(state, &path, &source)) {
+                Ok(module) => (Some(module), None),
+                Err(err) => {
+                    tracing::warn!("{}", err);
+                    (None, Some(err))
+                }
+            };
+
+        let inner = Arc::new(Mutex::new(ShaderInner {
+            path: path.clone(),
+            source,
+            module,
+            version: 0,
+            pending: None,
+            error,
+        }));
+        let watcher = watch(&path, Arc::downgrade(&inner), state.repaint_handle())?;
+        state.shaders.lock().unwrap().push(Arc::downgrade(&inner));
+        Ok(Self {
+            inner,
+            _watcher: Arc::new(watcher),
+        })
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.inner.lock().unwrap().path.clone()
+    }
+
+    // 最近一次重新加载失败的原因，成功加载后清除
+    pub fn error(&self) -> Option<ShaderError> {
+        self.inner.lock().unwrap().error.clone()
+    }
+
+    // 每次成功替换模块后加一
+    pub fn version(&self) -> u64 {
+        self.inner.lock().unwrap().version
+    }
+
+    // 返回最近一次编译成功的模块，文件更新后在这里创建新的模块；还没有成功编译过时返回 None
+    pub fn module(&self, state: &WgpuState) -> Option<ShaderModule> {
+        self.current(state).1
+    }
+
+    fn current(&self, state: &WgpuState) -> (u64, Option<ShaderModule>) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(source) = inner.pending.take() {
+            match create_module(state, &inner.path, &source) {
+                Ok(module) => {
+                    inner.module = Some(module);
+                    inner.version += 1;
+                    inner.error = None;
+                }
+                Err(err) => {
+                    tracing::warn!("{}", err);
+                    inner.error = Some(err);
+                }
+            }
+        }
+        (inner.version, inner.module.clone())
+    }
+
+    fn set_error(&self, error: ShaderError) {
+        tracing::warn!("{}", error);
+        self.inner.lock().unwrap().error = Some(error);
+    }
+}
+
+type BuildPipeline<P> = Box<dyn Fn(&WgpuState, &ShaderModule) -> P>;
+
+// 依赖 ShaderAsset 的管线（RenderPipeline、ComputePipeline 或自定义的组合），
+// 着色器更新后在下一次 get 时重新构建；构建失败时继续使用上一个管线
+pub struct ShaderPipeline<P> {
+    shader: ShaderAsset,
+    build: BuildPipeline<P>,
+    current: Mutex<(u64, Option<P>)>,
+}
+
+impl<P: Clone> ShaderPipeline<P> {
+    pub fn new(
+        state: &WgpuState,
+        shader: &ShaderAsset,
+        build: impl Fn(&WgpuState, &ShaderModule) -> P + 'static,
+    ) -> Self {
+        let (version, module) = shader.current(state);
+        let pipeline = module.map(|module| build(state, &module));
+        Self {
+            shader: shader.clone(),
+            build: Box::new(build),
+            current: Mutex::new((version, pipeline)),
+        }
+    }
+
+    pub fn shader(&self) -> &ShaderAsset {
+        &self.shader
+    }
+
+    // 可以在 render_frame 中调用，着色器还没有成功编译过时返回 None，这一帧应该跳过绘制
+    pub fn get(&self, state: &WgpuState) -> Option<P> {
+        let (version, module) = self.shader.current(state);
+        let mut current = self.current.lock().unwrap();
+        if current.0 != version
+            && let Some(module) = module
+        {
+            // 同一个版本只尝试构建一次
+            current.0 = version;
+            state.device.push_error_scope(ErrorFilter::Validation);
+            let pipeline = (self.build)(state, &module);
+            match pollster::block_on(state.device.pop_error_scope()) {
+                None => current.1 = Some(pipeline),
+                Some(err) => self.shader.set_error(ShaderError::new(
+                    &self.shader.path(),
+                    None,
+                    "Failed to rebuild pipeline",
+                    err.to_string(),
+                )),
+            }
+        }
+        current.1.clone()
+    }
+}
+
+fn validate(path: &Path, source: &str) -> Result<(), ShaderError> {
+    let path_name = path.to_string_lossy();
+    let module = naga::front::wgsl::parse_str(source).map_err(|err| {
+        ShaderError::new(
+            path,
+            err.location(source),
+            err.message(),
+            err.emit_to_string_with_path(source, path),
+        )
+    })?;
+    Validator::new(ValidationFlags::all(), Capabilities::all())
+        .validate(&module)
+        .map_err(|err| {
+            ShaderError::new(
+                path,
+                err.location(source),
+                err.as_inner().to_string(),
+                err.emit_to_string_with_path(source, &path_name),
+            )
+        })?;
+    Ok(())
+}
+
+// naga 的校验不包含设备相关的限制，所以创建模块时仍然用 error scope 捕获错误
+fn create_module(
+    state: &WgpuState,
+    path: &Path,
+    source: &str,
+) -> Result<ShaderModule, ShaderError> {
+    state.device.push_error_scope(ErrorFilter::Validation);
+    let module = state.device.create_shader_module(ShaderModuleDescriptor {
+        label: path.to_str(),
+        source: ShaderSource::Wgsl(source.into()),
+    });
+    match pollster::block_on(state.device.pop_error_scope()) {
+        None => Ok(module),
+        Some(err) => Err(ShaderError::new(
+            path,
+            None,
+            "Failed to create shader module",
+            err.to_string(),
+        )),
+    }
+}
+
+fn watch(
+    path: &Path,
+    inner: Weak<Mutex<ShaderInner>>,
+    repaint: Option<RepaintHandle>,
+) -> notify::Result<RecommendedWatcher> {
+    let file_name = path.file_name().map(ToOwned::to_owned);
+    let mut watcher = notify::recommended_watcher({
+        let path = path.to_path_buf();
+        move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                || !event
+                    .paths
+                    .iter()
+                    .any(|changed| changed.file_name() == file_name.as_deref())
+            {
+                return;
+            }
+            let Some(inner) = inner.upgrade() else {
+                return;
+            };
+            if reload(&path, &inner)
+                && let Some(repaint) = &repaint
+            {
+                repaint.request_repaint();
+            }
+        }
+    })?;
+    // 编辑器保存时经常先写临时文件再重命名，所以监听所在的目录
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    Ok(watcher)
+}
+
+// 在 watcher 线程上读取并校验新的源码，返回是否需要重绘
+fn reload(path: &Path, inner: &Mutex<ShaderInner>) -> bool {
+    // 保存的过程中文件可能暂时不存在或者为空
+    let Ok(source) = std::fs::read_to_string(path) else {
+        return false;
+    };
+    if source.trim().is_empty() || source == inner.lock().unwrap().source {
+        return false;
+    }
+    // 校验可能比较耗时，不持有锁，避免阻塞渲染线程上的 ShaderPipeline::get
+    let result = validate(path, &source);
+    let mut inner = inner.lock().unwrap();
+    // 校验期间可能已经处理过同样的内容
+    if source == inner.source {
+        return false;
+    }
+    match result {
+        Ok(()) => {
+            tracing::info!("Reloading shader {}", path.display());
+            inner.pending = Some(source.clone());
+        }
+        Err(err) => {
+            tracing::warn!("{}", err);
+            // 还没有应用的旧版本已经过时，继续使用当前的模块
+            inner.pending = None;
+            inner.error = Some(err);
+        }
+    }
+    inner.source = source;
+    true
+}
+
+// 在所有页面的 UI 之上显示本窗口着色器的编译错误
+pub(crate) fn show_errors(ctx: &egui::Context, state: &WgpuState) {
+    // 同时记录是否还有可以继续使用的模块
+    let errors: Vec<(ShaderError, bool)> = {
+        let mut shaders = state.shaders.lock().unwrap();
+        shaders.retain(|shader| shader.strong_count() > 0);
+        shaders
+            .iter()
+            .filter_map(Weak::upgrade)
+            .filter_map(|shader| {
+                let shader = shader.lock().unwrap();
+                Some((shader.error.clone()?, shader.module.is_some()))
+            })
+            .collect()
+    };
+    if errors.is_empty() {
+        return;
+    }
+    egui::Window::new("Shader Errors")
+        .anchor(egui::Align2::LEFT_BOTTOM, [8.0, -8.0])
+        .show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for (error, has_module) in &errors {
+                    ui.colored_label(ui.visuals().error_fg_color, error.to_string());
+                    ui.label(egui::RichText::new(&error.report).monospace());
+                    ui.label(if *has_module {
+                        "The last working version is still in use"
+                    } else {
+                        "Nothing is drawn with this shader until it compiles"
+                    });
+                }
+            });
+        });
+}
@@ -1,62 +1,757 @@
-use anyhow::anyhow;
-use std::sync::Arc;
+use anyhow::{anyhow, bail};
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
 use wgpu::{
-    Device, DeviceDescriptor, Instance, InstanceDescriptor, Queue, RequestAdapterOptionsBase,
-    Surface, SurfaceConfiguration,
+    Adapter, Backends, CompositeAlphaMode, Device, DeviceDescriptor, DownlevelFlags, Features,
+    Instance, InstanceDescriptor, Limits, PowerPreference, PresentMode, Queue,
+    RequestAdapterOptions, Surface, SurfaceConfiguration, SurfaceError, SurfaceTexture, Texture,
+    TextureFormat, TextureUsages, TextureView,
+};
+use winit::{
+    dpi::PhysicalSize,
+    window::{Window, WindowAttributes},
+};
+
+use crate::{
+    Render,
+    app::ui_render,
+    capture::{FrameCapture, Readback},
+    egui_utils::EguiRenderer,
+    error::{GpuError, GpuErrors},
+    event::RepaintHandle,
+    frame::FrameTimer,
+    navigator::Navigator,
+    page::Pages,
+    shader::ShaderRegistry,
 };
-use winit::{dpi::PhysicalSize, window::Window};
 
-use crate::egui_utils::EguiRenderer;
+type CaptureSender = oneshot::Sender<anyhow::Result<FrameCapture>>;
 
 pub struct WgpuState {
-    pub window: Arc<Window>,
-    pub surface: Surface<'static>,
+    // headless 模式下没有窗口和表面，改为渲染到 offscreen 纹理
+    pub window: Option<Arc<Window>>,
+    pub surface: Option<Surface<'static>>,
+    pub offscreen: Option<Texture>,
+    pub depth_texture: Option<Texture>,
+    pub depth_view: Option<TextureView>,
+    pub msaa_texture: Option<Texture>,
+    pub msaa_view: Option<TextureView>,
+    pub instance: Instance,
+    pub adapter: Adapter,
     pub device: Device,
     pub queue: Queue,
     pub config: SurfaceConfiguration,
+    pub options: GpuOptions,
     pub egui_renderer: EguiRenderer,
+    pub(crate) pending_captures: Mutex<Vec<CaptureSender>>,
+    pub(crate) frame_timer: FrameTimer,
+    // 响应式重绘模式下下一次需要重绘的时间
+    pub(crate) next_repaint: Option<Instant>,
+    pub(crate) repaint_handle: Option<RepaintHandle>,
+    pub(crate) navigator: Navigator,
+    pub(crate) errors: GpuErrors,
+    // 挂起期间没有表面，不进行渲染
+    pub(crate) suspended: bool,
+    // 窗口的取消令牌，页面的令牌都是它的子令牌
+    pub(crate) cancellation: CancellationToken,
+    pub(crate) shaders: ShaderRegistry,
 }
 
-impl WgpuState {
-    pub async fn new(window: Arc<Window>) -> anyhow::Result<Self> {
-        let instance = Instance::new(&InstanceDescriptor::from_env_or_default());
-        let surface = instance.create_surface(window.clone()).unwrap();
+// 创建 Instance/Adapter/Device 和配置表面时使用的选项
+#[derive(Clone, Debug)]
+pub struct GpuOptions {
+    // 为 None 时从 WGPU_BACKEND 等环境变量读取
+    pub backends: Option<Backends>,
+    pub power_preference: PowerPreference,
+    pub required_features: Features,
+    pub required_limits: Limits,
+    // 为 None 时使用表面的默认配置
+    pub present_mode: Option<PresentMode>,
+    // 为 Some 时由 WgpuState 创建深度/模板纹理，并在窗口大小变化时重建
+    pub depth_format: Option<TextureFormat>,
+    // 大于 1 时启用 MSAA，页面渲染到多重采样纹理后解析到表面
+    pub sample_count: u32,
+    // 只使用软件实现的 adapter，快照测试因此不受机器上 GPU 的影响
+    pub force_fallback_adapter: bool,
+}
 
-        let adapter = instance
-            .request_adapter(&RequestAdapterOptionsBase {
-                compatible_surface: Some(&surface),
-                ..Default::default()
+impl Default for GpuOptions {
+    fn default() -> Self {
+        Self {
+            backends: None,
+            power_preference: Default::default(),
+            required_features: Default::default(),
+            required_limits: Default::default(),
+            present_mode: None,
+            depth_format: None,
+            sample_count: 1,
+            force_fallback_adapter: false,
+        }
+    }
+}
+
+impl GpuOptions {
+    fn instance(&self) -> Instance {
+        let mut descriptor = InstanceDescriptor::from_env_or_default();
+        if let Some(backends) = self.backends {
+            descriptor.backends = backends;
+        }
+        Instance::new(&descriptor)
+    }
+
+    async fn request_adapter(
+        &self,
+        instance: &Instance,
+        compatible_surface: Option<&Surface<'static>>,
+    ) -> anyhow::Result<Adapter> {
+        if !self.force_fallback_adapter {
+            let adapter = instance
+                .request_adapter(&RequestAdapterOptions {
+                    power_preference: self.power_preference,
+                    compatible_surface,
+                    force_fallback_adapter: false,
+                })
+                .await;
+            if let Some(adapter) = adapter {
+                return Ok(adapter);
+            }
+        }
+
+        // 没有 GPU 时退回到软件实现的 adapter
+        instance
+            .request_adapter(&RequestAdapterOptions {
+                power_preference: self.power_preference,
+                compatible_surface,
+                force_fallback_adapter: true,
             })
             .await
-            .ok_or(anyhow!("Failed to find an appropriate adapter"))?;
+            .ok_or(anyhow!("Failed to find an appropriate adapter"))
+    }
+
+    async fn request_device(&self, adapter: &Adapter) -> anyhow::Result<(Device, Queue)> {
+        let name = adapter.get_info().name;
+
+        let missing = self.required_features - adapter.features();
+        if !missing.is_empty() {
+            bail!(
+                "Adapter \"{}\" does not support required features: {:?}",
+                name,
+                missing
+            );
+        }
+
+        let mut exceeded = Vec::new();
+        self.required_limits.check_limits_with_fail_fn(
+            &adapter.limits(),
+            false,
+            |limit, requested, allowed| {
+                exceeded.push(format!(
+                    "{} (requested {}, allowed {})",
+                    limit, requested, allowed
+                ))
+            },
+        );
+        if !exceeded.is_empty() {
+            bail!(
+                "Adapter \"{}\" does not support required limits: {}",
+                name,
+                exceeded.join(", ")
+            );
+        }
 
         let (device, queue) = adapter
-            .request_device(&DeviceDescriptor::default(), None)
+            .request_device(
+                &DeviceDescriptor {
+                    label: None,
+                    required_features: self.required_features,
+                    required_limits: self.required_limits.clone(),
+                    memory_hints: Default::default(),
+                },
+                None,
+            )
             .await?;
+        Ok((device, queue))
+    }
+}
+
+// 可在多个窗口之间共享的 GPU 资源
+#[derive(Clone)]
+pub struct GpuContext {
+    pub instance: Instance,
+    pub adapter: Adapter,
+    pub device: Device,
+    pub queue: Queue,
+    pub(crate) errors: GpuErrors,
+}
+
+impl WgpuState {
+    pub async fn new(window: Arc<Window>) -> anyhow::Result<Self> {
+        Self::with_options(window, GpuOptions::default()).await
+    }
+
+    pub async fn with_options(window: Arc<Window>, options: GpuOptions) -> anyhow::Result<Self> {
+        let instance = options.instance();
+        let surface = instance.create_surface(window.clone())?;
+
+        let adapter = options.request_adapter(&instance, Some(&surface)).await?;
+        let (device, queue) = options.request_device(&adapter).await?;
+        let errors = GpuErrors::install(&device);
+
+        Self::from_surface(
+            window,
+            surface,
+            GpuContext {
+                instance,
+                adapter,
+                device,
+                queue,
+                errors,
+            },
+            options,
+        )
+    }
+
+    // 为新窗口创建表面，复用已有的 Device/Queue
+    pub fn with_gpu(
+        window: Arc<Window>,
+        gpu: GpuContext,
+        options: GpuOptions,
+    ) -> anyhow::Result<Self> {
+        let surface = gpu.instance.create_surface(window.clone())?;
+        if !gpu.adapter.is_surface_supported(&surface) {
+            bail!("The shared adapter does not support this window's surface");
+        }
+        Self::from_surface(window, surface, gpu, options)
+    }
+
+    fn from_surface(
+        window: Arc<Window>,
+        surface: Surface<'static>,
+        GpuContext {
+            instance,
+            adapter,
+            device,
+            queue,
+            errors,
+        }: GpuContext,
+        options: GpuOptions,
+    ) -> anyhow::Result<Self> {
         let PhysicalSize { width, height } = window.inner_size();
 
-        let config = surface
+        let mut config = surface
             .get_default_config(&adapter, width.max(1), height.max(1))
             .ok_or(anyhow!("Failed to find a surface configuration"))?;
+        // 截图需要从表面纹理复制数据
+        if surface
+            .get_capabilities(&adapter)
+            .usages
+            .contains(TextureUsages::COPY_SRC)
+        {
+            config.usage |= TextureUsages::COPY_SRC;
+        }
+        if let Some(present_mode) = options.present_mode {
+            let supported = surface.get_capabilities(&adapter).present_modes;
+            if !supported.contains(&present_mode) {
+                bail!(
+                    "Present mode {:?} is not supported, available modes: {:?}",
+                    present_mode,
+                    supported
+                );
+            }
+            config.present_mode = present_mode;
+        }
+
+        // 先检查 MSAA 采样数，避免之后创建纹理时才产生校验错误
+        check_sample_count(&adapter, &device, &config, &options)?;
 
         surface.configure(&device, &config);
 
-        let egui_renderer = EguiRenderer::new(&device, config.format, None, 1, &window);
+        // egui 在 MSAA 解析之后直接绘制到表面上，所以始终使用单重采样
+        let egui_renderer = EguiRenderer::new(&device, config.format, None, 1, Some(&window));
+        // 其他线程调用 egui::Context::request_repaint 时唤醒窗口
+        egui_renderer.context().set_request_repaint_callback({
+            let window = window.clone();
+            move |info| {
+                if info.delay.is_zero() {
+                    window.request_redraw();
+                }
+            }
+        });
+        let (depth_texture, depth_view) = create_depth_texture(&device, &config, &options);
+        let (msaa_texture, msaa_view) = create_msaa_texture(&device, &config, &options);
 
         Ok(Self {
-            window,
-            surface,
+            window: Some(window),
+            surface: Some(surface),
+            offscreen: None,
+            depth_texture,
+            depth_view,
+            msaa_texture,
+            msaa_view,
+            instance,
+            adapter,
             device,
             queue,
             config,
+            options,
             egui_renderer,
+            pending_captures: Mutex::new(Vec::new()),
+            frame_timer: FrameTimer::new(),
+            next_repaint: None,
+            repaint_handle: None,
+            navigator: Navigator::default(),
+            errors,
+            suspended: false,
+            cancellation: CancellationToken::new(),
+            shaders: Default::default(),
         })
     }
 
+    pub fn gpu(&self) -> GpuContext {
+        GpuContext {
+            instance: self.instance.clone(),
+            adapter: self.adapter.clone(),
+            device: self.device.clone(),
+            queue: self.queue.clone(),
+            errors: self.errors.clone(),
+        }
+    }
+
+    // 不依赖窗口的构造方式，每帧渲染到自有的 offscreen 纹理，可在 CI 或无显示器的机器上运行
+    pub async fn headless(width: u32, height: u32) -> anyhow::Result<Self> {
+        Self::headless_with_options(width, height, GpuOptions::default()).await
+    }
+
+    pub async fn headless_with_options(
+        width: u32,
+        height: u32,
+        options: GpuOptions,
+    ) -> anyhow::Result<Self> {
+        let instance = options.instance();
+        let adapter = options.request_adapter(&instance, None).await?;
+        tracing::info!("Headless adapter: {:?}", adapter.get_info());
+
+        let (device, queue) = options.request_device(&adapter).await?;
+        let errors = GpuErrors::install(&device);
+
+        let config = SurfaceConfiguration {
+            usage: TextureUsages::RENDER_ATTACHMENT
+                | TextureUsages::COPY_SRC
+                | TextureUsages::TEXTURE_BINDING,
+            format: TextureFormat::Rgba8UnormSrgb,
+            width: width.max(1),
+            height: height.max(1),
+            present_mode: options.present_mode.unwrap_or(PresentMode::Fifo),
+            desired_maximum_frame_latency: 2,
+            alpha_mode: CompositeAlphaMode::Opaque,
+            view_formats: vec![],
+        };
+        check_sample_count(&adapter, &device, &config, &options)?;
+
+        let offscreen = create_offscreen_texture(&device, &config);
+        let (depth_texture, depth_view) = create_depth_texture(&device, &config, &options);
+        let (msaa_texture, msaa_view) = create_msaa_texture(&device, &config, &options);
+
+        let egui_renderer = EguiRenderer::new(&device, config.format, None, 1, None);
+
+        Ok(Self {
+            window: None,
+            surface: None,
+            offscreen: Some(offscreen),
+            depth_texture,
+            depth_view,
+            msaa_texture,
+            msaa_view,
+            instance,
+            adapter,
+            device,
+            queue,
+            config,
+            options,
+            egui_renderer,
+            pending_captures: Mutex::new(Vec::new()),
+            frame_timer: FrameTimer::new(),
+            next_repaint: None,
+            repaint_handle: None,
+            navigator: Navigator::default(),
+            errors,
+            suspended: false,
+            cancellation: CancellationToken::new(),
+            shaders: Default::default(),
+        })
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.window.is_none()
+    }
+
+    // 应用挂起时释放表面（Android 上原生窗口会随之销毁），Device、Queue 和页面都保留
+    pub fn suspend(&mut self) {
+        self.surface = None;
+        self.suspended = true;
+    }
+
+    // 为窗口重新创建表面，并按窗口当前的尺寸重新配置。
+    // headless 模式下按挂起期间最后一次 resize 的尺寸重新创建 offscreen 等渲染目标
+    pub fn resume(&mut self) -> anyhow::Result<()> {
+        if !self.suspended {
+            return Ok(());
+        }
+        let size = match self.window.clone() {
+            Some(window) => {
+                let surface = self.instance.create_surface(window.clone())?;
+                if !self.adapter.is_surface_supported(&surface) {
+                    bail!("The adapter does not support the recreated surface");
+                }
+                self.surface = Some(surface);
+                window.inner_size()
+            }
+            None => PhysicalSize::new(self.config.width, self.config.height),
+        };
+        self.resize(size);
+        self.suspended = false;
+        Ok(())
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+
+    pub fn scale_factor(&self) -> f64 {
+        self.window
+            .as_ref()
+            .map(|window| window.scale_factor())
+            .unwrap_or(1.0)
+    }
+
     pub fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
         self.config.width = size.width.max(1);
         self.config.height = size.height.max(1);
         // 重新配置表面（更新尺寸）
-        self.surface.configure(&self.device, &self.config);
+        if let Some(surface) = &self.surface {
+            surface.configure(&self.device, &self.config);
+        }
+        if self.offscreen.is_some() {
+            self.offscreen = Some(create_offscreen_texture(&self.device, &self.config));
+        }
+        (self.depth_texture, self.depth_view) =
+            create_depth_texture(&self.device, &self.config, &self.options);
+        (self.msaa_texture, self.msaa_view) =
+            create_msaa_texture(&self.device, &self.config, &self.options);
+    }
+
+    // 请求重绘窗口，可在页面的 update、handle_event 等处调用
+    pub fn request_repaint(&self) {
+        match (&self.repaint_handle, &self.window) {
+            (Some(handle), _) => handle.request_repaint(),
+            (None, Some(window)) => window.request_redraw(),
+            (None, None) => {}
+        }
+    }
+
+    // 后台任务可以持有此句柄，在完成后唤醒事件循环重绘本窗口
+    pub fn repaint_handle(&self) -> Option<RepaintHandle> {
+        self.repaint_handle.clone()
+    }
+
+    pub(crate) fn set_repaint_handle(&mut self, handle: RepaintHandle) {
+        self.egui_renderer.context().set_request_repaint_callback({
+            let handle = handle.clone();
+            move |info| {
+                if info.delay.is_zero() {
+                    handle.request_repaint();
+                }
+            }
+        });
+        self.navigator.set_repaint_handle(handle.clone());
+        self.repaint_handle = Some(handle);
     }
+
+    // 页面可以在 new 中保存此句柄，之后在 update 或 ui_draw 中切换页面
+    pub fn navigator(&self) -> Navigator {
+        self.navigator.clone()
+    }
+
+    // 窗口关闭或应用退出时取消，可以在后台任务中监听
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
+    // 关闭窗口，不再询问页面的 on_close_requested；主窗口关闭时应用退出
+    pub fn close_window(&self) {
+        if let Some(handle) = &self.repaint_handle {
+            handle.request_close();
+        }
+    }
+
+    // 打开新窗口，新窗口与本窗口共享 Device；headless 模式下没有事件循环，返回错误
+    pub fn open_window(
+        &self,
+        attributes: WindowAttributes,
+        setup: impl FnOnce(&mut Pages) -> anyhow::Result<()> + Send + 'static,
+    ) -> anyhow::Result<()> {
+        let handle = self
+            .repaint_handle
+            .as_ref()
+            .ok_or(anyhow!("WgpuState has no event loop to open a window"))?;
+        handle.open_window(attributes, setup);
+        Ok(())
+    }
+
+    pub fn set_fixed_timestep(&mut self, step: Option<Duration>) {
+        self.frame_timer.fixed_timestep = step.filter(|step| !step.is_zero());
+    }
+
+    // 固定每帧的时间间隔，FrameContext 的 delta、elapsed 和 alpha 不再取决于墙钟；None 恢复使用墙钟
+    pub fn set_frame_delta(&mut self, delta: Option<Duration>) {
+        self.frame_timer.frame_delta = delta;
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.options.sample_count.max(1)
+    }
+
+    // 创建渲染管线时使用，与 WgpuState 的采样数保持一致
+    pub fn multisample_state(&self) -> wgpu::MultisampleState {
+        wgpu::MultisampleState {
+            count: self.sample_count(),
+            ..Default::default()
+        }
+    }
+
+    // 启用 MSAA 时渲染到多重采样纹理，并自动解析到 view
+    pub fn color_attachment<'a>(
+        &'a self,
+        view: &'a TextureView,
+        ops: wgpu::Operations<wgpu::Color>,
+    ) -> wgpu::RenderPassColorAttachment<'a> {
+        match &self.msaa_view {
+            Some(msaa_view) => wgpu::RenderPassColorAttachment {
+                view: msaa_view,
+                resolve_target: Some(view),
+                ops,
+            },
+            None => wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops,
+            },
+        }
+    }
+
+    // 供页面在渲染通道中挂载 WgpuState 管理的深度缓冲
+    pub fn depth_stencil_attachment(
+        &self,
+        depth_ops: Option<wgpu::Operations<f32>>,
+        stencil_ops: Option<wgpu::Operations<u32>>,
+    ) -> Option<wgpu::RenderPassDepthStencilAttachment<'_>> {
+        self.depth_view
+            .as_ref()
+            .map(|view| wgpu::RenderPassDepthStencilAttachment {
+                view,
+                depth_ops,
+                stencil_ops,
+            })
+    }
+
+    // 获取当前帧的渲染目标，窗口模式下还会返回需要 present 的表面纹理。
+    // 返回 None 表示跳过这一帧，需要重试时会设置 next_repaint，否则等待下一次 Resized 或输入事件
+    pub(crate) fn current_target(
+        &mut self,
+    ) -> anyhow::Result<Option<(Option<SurfaceTexture>, TextureView)>> {
+        if let Some(surface) = &self.surface {
+            let surface_texture = match self.acquire(surface)? {
+                Acquire::Texture(surface_texture) => surface_texture,
+                Acquire::Skip(retry) => {
+                    self.next_repaint = retry.and_then(|retry| Instant::now().checked_add(retry));
+                    return Ok(None);
+                }
+            };
+            let view = surface_texture.texture.create_view(&Default::default());
+            return Ok(Some((Some(surface_texture), view)));
+        }
+        let texture = self.offscreen.as_ref().ok_or(anyhow!(
+            "WgpuState has neither a surface nor an offscreen target"
+        ))?;
+        Ok(Some((None, texture.create_view(&Default::default()))))
+    }
+
+    fn acquire(&self, surface: &Surface<'static>) -> Result<Acquire, GpuError> {
+        let mut reconfigured = false;
+        loop {
+            match surface.get_current_texture() {
+                Ok(surface_texture) => return Ok(Acquire::Texture(surface_texture)),
+                // 表面过期（窗口尺寸变化）或丢失时重新配置，再试一次
+                Err(SurfaceError::Lost | SurfaceError::Outdated) if !reconfigured => {
+                    tracing::debug!("Surface lost or outdated, reconfiguring");
+                    surface.configure(&self.device, &self.config);
+                    reconfigured = true;
+                }
+                // 重新配置后仍然不可用（例如窗口最小化），不再主动重绘，等待下一次 Resized
+                Err(SurfaceError::Lost | SurfaceError::Outdated) => {
+                    tracing::debug!("Surface still lost or outdated, waiting for resize");
+                    return Ok(Acquire::Skip(None));
+                }
+                Err(SurfaceError::OutOfMemory) => return Err(GpuError::OutOfMemory),
+                Err(SurfaceError::Timeout) => {
+                    tracing::debug!("Surface timed out, skipping frame");
+                    return Ok(Acquire::Skip(Some(SKIPPED_FRAME_RETRY)));
+                }
+                Err(err) => {
+                    tracing::warn!("Failed to acquire surface texture, skipping frame: {}", err);
+                    return Ok(Acquire::Skip(Some(SKIPPED_FRAME_RETRY)));
+                }
+            }
+        }
+    }
+
+    // 在下一帧渲染完成后返回该帧的画面
+    pub fn capture_frame(
+        &self,
+    ) -> impl Future<Output = anyhow::Result<FrameCapture>> + Send + 'static {
+        let (sender, receiver) = oneshot::channel();
+        self.pending_captures.lock().unwrap().push(sender);
+        if let Some(window) = &self.window {
+            window.request_redraw();
+        }
+        async move {
+            receiver
+                .await
+                .map_err(|_| anyhow!("Frame capture was cancelled"))?
+        }
+    }
+
+    // 直接读取 offscreen 纹理中最近一次渲染的结果
+    pub fn capture_offscreen(&self) -> anyhow::Result<FrameCapture> {
+        let texture = self
+            .offscreen
+            .as_ref()
+            .ok_or(anyhow!("WgpuState has no offscreen target"))?;
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        let readback = Readback::copy(&self.device, &mut encoder, texture)?;
+        self.queue.submit(std::iter::once(encoder.finish()));
+        readback.finish(&self.device)
+    }
+
+    // 与窗口事件循环中相同的方式渲染一帧，主要用于 headless 模式
+    pub fn render_frame(&mut self, ui: &mut dyn Render) -> anyhow::Result<()> {
+        ui_render(self, ui).map(|_| ())
+    }
+}
+
+// 跳过一帧后重试的间隔，避免表面持续不可用时反复重绘占满 CPU
+const SKIPPED_FRAME_RETRY: Duration = Duration::from_millis(100);
+
+enum Acquire {
+    Texture(SurfaceTexture),
+    // 跳过这一帧，Some 表示在指定时间后重试
+    Skip(Option<Duration>),
+}
+
+fn create_depth_texture(
+    device: &Device,
+    config: &SurfaceConfiguration,
+    options: &GpuOptions,
+) -> (Option<Texture>, Option<TextureView>) {
+    let Some(format) = options.depth_format else {
+        return (None, None);
+    };
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("depth_texture"),
+        size: wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: options.sample_count.max(1),
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&Default::default());
+    (Some(texture), Some(view))
+}
+
+fn create_msaa_texture(
+    device: &Device,
+    config: &SurfaceConfiguration,
+    options: &GpuOptions,
+) -> (Option<Texture>, Option<TextureView>) {
+    if options.sample_count <= 1 {
+        return (None, None);
+    }
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("msaa_texture"),
+        size: wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: options.sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format: config.format,
+        usage: TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&Default::default());
+    (Some(texture), Some(view))
+}
+
+// 与 wgpu 创建纹理时的校验一致：只有启用 TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
+// 或者 adapter 不完全支持 WebGPU 时才使用 adapter 报告的采样数，否则只能使用 WebGPU 保证的采样数
+fn check_sample_count(
+    adapter: &Adapter,
+    device: &Device,
+    config: &SurfaceConfiguration,
+    options: &GpuOptions,
+) -> anyhow::Result<()> {
+    let features = device.features();
+    let adapter_specific = features.contains(Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+        || !adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(DownlevelFlags::WEBGPU_TEXTURE_FORMAT_SUPPORT);
+    let mut formats = vec![config.format];
+    formats.extend(options.depth_format);
+    for format in formats {
+        let flags = if adapter_specific {
+            adapter.get_texture_format_features(format).flags
+        } else {
+            format.guaranteed_format_features(features).flags
+        };
+        if !flags.sample_count_supported(options.sample_count.max(1)) {
+            bail!(
+                "Sample count {} is not supported for {:?}, supported counts: {:?}",
+                options.sample_count,
+                format,
+                flags.supported_sample_counts()
+            );
+        }
+    }
+    Ok(())
+}
+
+fn create_offscreen_texture(device: &Device, config: &SurfaceConfiguration) -> Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("offscreen_target"),
+        size: wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: config.format,
+        usage: config.usage,
+        view_formats: &[],
+    })
 }
@@ -0,0 +1,238 @@
+use futures_core::Stream;
+use notify::{RecursiveMode, Watcher};
+use std::{
+    collections::HashMap,
+    future::{Future, poll_fn},
+    hash::{DefaultHasher, Hash, Hasher},
+    path::PathBuf,
+    pin::{Pin, pin},
+    time::{Duration, Instant},
+};
+use tokio::{
+    runtime::Handle,
+    sync::{broadcast, mpsc::Sender, watch},
+    task::JoinHandle,
+    time::MissedTickBehavior,
+};
+
+type Run<M> = Box<dyn FnOnce(Sender<M>) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send>;
+
+struct Recipe<M> {
+    id: u64,
+    run: Run<M>,
+}
+
+// Page::subscriptions 返回的长期输入源。框架每帧比较 id，只启动新增的任务并停止消失的任务，
+// 同一个 id 在任务运行期间再次返回时会被忽略
+pub struct Subscription<M> {
+    recipes: Vec<Recipe<M>>,
+}
+
+fn hash_id(kind: &str, id: impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    kind.hash(&mut hasher);
+    id.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<M: Send + 'static> Subscription<M> {
+    pub fn none() -> Self {
+        Self {
+            recipes: Vec::new(),
+        }
+    }
+
+    fn run<F>(kind: &str, id: impl Hash, run: impl FnOnce(Sender<M>) -> F + Send + 'static) -> Self
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        Self {
+            recipes: vec![Recipe {
+                id: hash_id(kind, id),
+                run: Box::new(move |sender| Box::pin(run(sender))),
+            }],
+        }
+    }
+
+    // 每隔 period 发送一次消息，第一次在 period 之后
+    pub fn interval(
+        id: impl Hash,
+        period: Duration,
+        f: impl Fn(Instant) -> M + Send + 'static,
+    ) -> Self {
+        Self::run("interval", id, move |sender| async move {
+            let start = tokio::time::Instant::now() + period;
+            let mut interval = tokio::time::interval_at(start, period);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            loop {
+                let instant = interval.tick().await;
+                if sender.send(f(instant.into_std())).await.is_err() {
+                    break;
+                }
+            }
+        })
+    }
+
+    // 启动时发送当前值，之后每次变化发送一次
+    pub fn watch<T>(
+        id: impl Hash,
+        mut receiver: watch::Receiver<T>,
+        f: impl Fn(T) -> M + Send + 'static,
+    ) -> Self
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        Self::run("watch", id, move |sender| async move {
+            loop {
+                let value = receiver.borrow_and_update().clone();
+                if sender.send(f(value)).await.is_err() || receiver.changed().await.is_err() {
+                    break;
+                }
+            }
+        })
+    }
+
+    // 处理不过来时跳过落后的值
+    pub fn broadcast<T>(
+        id: impl Hash,
+        mut receiver: broadcast::Receiver<T>,
+        f: impl Fn(T) -> M + Send + 'static,
+    ) -> Self
+    where
+        T: Clone + Send + 'static,
+    {
+        Self::run("broadcast", id, move |sender| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(value) => {
+                        if sender.send(f(value)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!("Subscription skipped {} broadcast values", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    pub fn stream(id: impl Hash, stream: impl Stream<Item = M> + Send + 'static) -> Self {
+        Self::run("stream", id, move |sender| async move {
+            let mut stream = pin!(stream);
+            while let Some(message) = poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+                if sender.send(message).await.is_err() {
+                    break;
+                }
+            }
+        })
+    }
+
+    // 监听文件或目录的变化，停止订阅时同时停止监听
+    pub fn watch_path(
+        path: impl Into<PathBuf>,
+        recursive: bool,
+        f: impl Fn(notify::Event) -> M + Send + 'static,
+    ) -> Self {
+        let path = path.into();
+        Self::run(
+            "watch_path",
+            (path.clone(), recursive),
+            move |sender| async move {
+                let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+                let watcher = notify::recommended_watcher(move |event| {
+                    let _ = tx.send(event);
+                })
+                .and_then(|mut watcher| {
+                    let mode = if recursive {
+                        RecursiveMode::Recursive
+                    } else {
+                        RecursiveMode::NonRecursive
+                    };
+                    watcher.watch(&path, mode)?;
+                    Ok(watcher)
+                });
+                let _watcher = match watcher {
+                    Ok(watcher) => watcher,
+                    Err(err) => {
+                        tracing::error!("Failed to watch {}: {}", path.display(), err);
+                        return;
+                    }
+                };
+                while let Some(event) = rx.recv().await {
+                    match event {
+                        Ok(event) => {
+                            if sender.send(f(event)).await.is_err() {
+                                break;
+                            }
+                        }
+                        Err(err) => tracing::warn!("Watch error on {}: {}", path.display(), err),
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn batch(subscriptions: impl IntoIterator<Item = Subscription<M>>) -> Self {
+        Self {
+            recipes: subscriptions
+                .into_iter()
+                .flat_map(|subscription| subscription.recipes)
+                .collect(),
+        }
+    }
+}
+
+impl<M: Send + 'static> Default for Subscription<M> {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+// 页面正在运行的订阅任务，drop 时全部停止
+pub(crate) struct SubscriptionTracker {
+    running: HashMap<u64, JoinHandle<()>>,
+}
+
+impl SubscriptionTracker {
+    pub(crate) fn new() -> Self {
+        Self {
+            running: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn update<M: Send + 'static>(
+        &mut self,
+        subscription: Subscription<M>,
+        sender: &Sender<M>,
+        handle: &Handle,
+    ) {
+        let mut running = HashMap::with_capacity(subscription.recipes.len());
+        for recipe in subscription.recipes {
+            if running.contains_key(&recipe.id) {
+                continue;
+            }
+            let task = self
+                .running
+                .remove(&recipe.id)
+                .unwrap_or_else(|| handle.spawn((recipe.run)(sender.clone())));
+            running.insert(recipe.id, task);
+        }
+        // 剩下的是本帧不再需要的订阅
+        self.stop();
+        self.running = running;
+    }
+
+    pub(crate) fn stop(&mut self) {
+        for (_, task) in self.running.drain() {
+            task.abort();
+        }
+    }
+}
+
+impl Drop for SubscriptionTracker {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,351 @@
+use crate::{
+    FrameCapture, GpuOptions, NavigationUi, Page, Pages, Params, Render, Route, WgpuState,
+};
+use anyhow::{anyhow, bail};
+use std::{
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::Arc,
+    task::{Wake, Waker},
+    thread::Thread,
+    time::{Duration, Instant},
+};
+use tokio::runtime::Runtime;
+use winit::{dpi::PhysicalSize, event::WindowEvent};
+
+// 在 headless WgpuState 上通过 Pages 托管单个页面，与 App 中的生命周期、消息和命令处理方式相同。
+// 用脚本化的事件和消息驱动，并与参考图像对比
+pub struct PageHarness<T: Page + 'static> {
+    pub state: WgpuState,
+    pub pages: Pages,
+    // leave 离开的路由，enter 时返回
+    left: Option<Route>,
+    // 执行页面命令的独立运行时，测试不需要运行在 tokio 中
+    runtime: Option<Runtime>,
+    snapshot_dir: PathBuf,
+    _page: PhantomData<T>,
+}
+
+// wait_for_commands 等待页面命令的最长时间
+pub const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+
+// 页面收到消息或命令完成时唤醒等待中的测试线程
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+impl<T: Page + 'static> PageHarness<T> {
+    // 使用软件实现的 adapter，每帧按 60 Hz 推进时间，渲染结果不受测试机器的 GPU 和速度影响
+    pub fn new(width: u32, height: u32) -> anyhow::Result<Self> {
+        let options = GpuOptions {
+            force_fallback_adapter: true,
+            ..Default::default()
+        };
+        Self::with_options(width, height, options)
+    }
+
+    pub fn with_options(width: u32, height: u32, options: GpuOptions) -> anyhow::Result<Self> {
+        let mut state =
+            pollster::block_on(WgpuState::headless_with_options(width, height, options))?;
+        state.set_frame_delta(Some(Duration::from_secs(1) / 60));
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()?;
+
+        let mut pages = Pages::new();
+        pages.register::<T, T::Message>()?;
+        pages.set_navigation(NavigationUi::None);
+        pages.set_waker(Waker::from(Arc::new(ThreadWaker(std::thread::current()))));
+        {
+            let _guard = runtime.enter();
+            pages.create(&state, runtime.handle());
+            // 与 App 一样，页面在第一帧之前成为当前页面
+            pages.sync_current(&state);
+        }
+
+        // cargo test 运行时会设置 CARGO_MANIFEST_DIR
+        let snapshot_dir = std::env::var_os("CARGO_MANIFEST_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_default()
+            .join("tests")
+            .join("snapshots");
+
+        Ok(Self {
+            state,
+            pages,
+            left: None,
+            runtime: Some(runtime),
+            snapshot_dir,
+            _page: PhantomData,
+        })
+    }
+
+    pub fn snapshot_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.snapshot_dir = dir.into();
+        self
+    }
+
+    // None 时改用墙钟
+    pub fn frame_delta(mut self, delta: Option<Duration>) -> Self {
+        self.state.set_frame_delta(delta);
+        self
+    }
+
+    pub fn page(&self) -> &T {
+        self.pages.page::<T>().expect("page is not loaded")
+    }
+
+    pub fn page_mut(&mut self) -> &mut T {
+        self.pages.page_mut::<T>().expect("page is not loaded")
+    }
+
+    // 页面可能在 new、update 等回调中直接调用 tokio::spawn，需要进入运行时上下文
+    fn with_pages<R>(&mut self, f: impl FnOnce(&mut Pages, &mut WgpuState) -> R) -> R {
+        let _guard = self.runtime.as_ref().map(|runtime| runtime.enter());
+        f(&mut self.pages, &mut self.state)
+    }
+
+    // 与 App 中相同的处理方式：先更新 WgpuState，再交给页面
+    pub fn event(&mut self, event: WindowEvent) -> &mut Self {
+        self.with_pages(|pages, state| {
+            if let WindowEvent::Resized(size) = event {
+                state.resize(size);
+                pages.resize(size, state);
+            }
+            pages.handle_event(event, state);
+        });
+        self
+    }
+
+    // 模拟切换到其他页面再切换回来
+    // 离开期间页面保留但不是当前页面，不会渲染，订阅也会停止
+    pub fn leave(&mut self) -> &mut Self {
+        if let Some(route) = self.with_pages(|pages, state| pages.leave_current(state, false)) {
+            self.left = Some(route);
+        }
+        self
+    }
+
+    pub fn enter(&mut self) -> &mut Self {
+        if let Some(route) = self.left.take() {
+            self.pages.current = route.id;
+            self.pages.params = route.params;
+        }
+        self.with_pages(|pages, state| pages.sync_current(state));
+        self
+    }
+
+    // 模拟带参数进入页面，例如 Navigator::push("scene?file=foo.gltf")
+    pub fn enter_with(&mut self, params: Params) -> &mut Self {
+        if let Some(route) = self.left.take() {
+            self.pages.current = route.id;
+        }
+        self.pages.params = params;
+        self.with_pages(|pages, state| pages.sync_current(state));
+        self
+    }
+
+    // 模拟应用挂起和恢复，与 App 一样挂起时只释放渲染目标，挂起期间 render_frames 不会渲染新的画面
+    pub fn suspend(&mut self) -> &mut Self {
+        self.with_pages(|pages, state| {
+            pages.suspend(state);
+            state.suspend();
+        });
+        self
+    }
+
+    pub fn resume(&mut self) -> anyhow::Result<&mut Self> {
+        self.with_pages(|pages, state| {
+            let size = PhysicalSize::new(state.config.width, state.config.height);
+            state.resume()?;
+            let new_size = PhysicalSize::new(state.config.width, state.config.height);
+            if new_size != size {
+                pages.resize(new_size, state);
+            }
+            pages.resume(state);
+            Ok::<_, anyhow::Error>(())
+        })?;
+        Ok(self)
+    }
+
+    // 模拟 UnloadOnLeave：离开并销毁页面，重新构建后恢复 snapshot 再进入
+    pub fn reload(&mut self) -> &mut Self {
+        if let Some(route) = self.with_pages(|pages, state| pages.leave_current(state, true)) {
+            self.left = Some(route);
+        }
+        self.enter()
+    }
+
+    // 模拟用户关闭窗口，返回页面是否允许关闭
+    pub fn close_requested(&mut self) -> bool {
+        self.with_pages(|pages, state| pages.close_requested(state))
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) -> &mut Self {
+        self.event(WindowEvent::Resized(PhysicalSize::new(width, height)))
+    }
+
+    pub fn egui_event(&mut self, event: egui::Event) -> &mut Self {
+        self.state.egui_renderer.pending_events.push(event);
+        self
+    }
+
+    // 像后台任务一样通过页面的 sender 发送消息，并立即处理。
+    // 消息队列已满时 panic，测试中不应该悄悄丢弃输入
+    pub fn message(&mut self, message: T::Message) -> &mut Self {
+        let sender = self.pages.sender::<T>().expect("page is not loaded");
+        if let Err(err) = sender.try_send(message) {
+            panic!("Failed to send message to the page: {}", err);
+        }
+        self.flush_messages()
+    }
+
+    // 处理页面收到的消息、已经完成的命令和 GPU 校验错误，并同步订阅
+    pub fn flush_messages(&mut self) -> &mut Self {
+        self.with_pages(|pages, state| {
+            pages.process_messages(state);
+            for error in state.errors.take() {
+                pages.report_error(anyhow::Error::new(error), state);
+            }
+        });
+        self
+    }
+
+    // 阻塞直到所有命令执行完毕，期间产生的消息和新命令也会被处理；超过 DEFAULT_COMMAND_TIMEOUT 时返回错误
+    pub fn wait_for_commands(&mut self) -> anyhow::Result<&mut Self> {
+        self.wait_for_commands_timeout(DEFAULT_COMMAND_TIMEOUT)
+    }
+
+    pub fn wait_for_commands_timeout(&mut self, timeout: Duration) -> anyhow::Result<&mut Self> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            self.flush_messages();
+            if !self.pages.has_pending_commands() {
+                return Ok(self);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                bail!("Page commands did not finish within {:?}", timeout);
+            }
+            // 命令完成时通过 waker 唤醒，超时只是为了防止错过唤醒
+            std::thread::park_timeout((deadline - now).min(Duration::from_millis(100)));
+        }
+    }
+
+    // 渲染 n 帧并返回最后一帧的画面
+    pub fn render_frames(&mut self, n: usize) -> anyhow::Result<FrameCapture> {
+        for _ in 0..n.max(1) {
+            self.flush_messages();
+            self.with_pages(|pages, state| state.render_frame(pages))?;
+        }
+        self.state.capture_offscreen()
+    }
+
+    pub fn assert_snapshot(
+        &mut self,
+        name: &str,
+        frames: usize,
+        tolerance: u8,
+    ) -> anyhow::Result<()> {
+        let frame = self.render_frames(frames)?;
+        compare_snapshot(&frame, &self.snapshot_dir, name, tolerance)
+    }
+}
+
+// 将画面与 `{dir}/{name}.png` 对比，任一通道差值超过 tolerance 即视为失败。
+// 失败时写出 `{name}.new.png` 和 `{name}.diff.png`；设置 UPDATE_SNAPSHOTS=1 时直接更新参考图像
+pub fn compare_snapshot(
+    frame: &FrameCapture,
+    dir: &Path,
+    name: &str,
+    tolerance: u8,
+) -> anyhow::Result<()> {
+    let reference_path = dir.join(format!("{}.png", name));
+    let new_path = dir.join(format!("{}.new.png", name));
+    let diff_path = dir.join(format!("{}.diff.png", name));
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some_and(|v| v != "0") {
+        std::fs::create_dir_all(dir)?;
+        frame.save_png(&reference_path)?;
+        let _ = std::fs::remove_file(&new_path);
+        let _ = std::fs::remove_file(&diff_path);
+        return Ok(());
+    }
+
+    if !reference_path.exists() {
+        std::fs::create_dir_all(dir)?;
+        frame.save_png(&new_path)?;
+        bail!(
+            "Missing reference image {}, wrote {} (rerun with UPDATE_SNAPSHOTS=1 to accept it)",
+            reference_path.display(),
+            new_path.display()
+        );
+    }
+
+    let reference = FrameCapture::load_png(&reference_path)
+        .map_err(|err| anyhow!("Failed to load {}: {}", reference_path.display(), err))?;
+    if (reference.width, reference.height) != (frame.width, frame.height) {
+        frame.save_png(&new_path)?;
+        bail!(
+            "Snapshot {} size mismatch: expected {}x{}, got {}x{}",
+            name,
+            reference.width,
+            reference.height,
+            frame.width,
+            frame.height
+        );
+    }
+
+    let mut mismatched = 0;
+    let mut diff = Vec::with_capacity(frame.rgba.len());
+    for (actual, expected) in frame.rgba.chunks(4).zip(reference.rgba.chunks(4)) {
+        let exceeded = actual
+            .iter()
+            .zip(expected)
+            .any(|(a, e)| a.abs_diff(*e) > tolerance);
+        if exceeded {
+            mismatched += 1;
+            diff.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            // 未变化的像素以变暗的灰度显示，方便定位差异
+            let gray = ((expected[0] as u32 + expected[1] as u32 + expected[2] as u32) / 12) as u8;
+            diff.extend_from_slice(&[gray, gray, gray, 255]);
+        }
+    }
+
+    if mismatched == 0 {
+        let _ = std::fs::remove_file(&new_path);
+        let _ = std::fs::remove_file(&diff_path);
+        return Ok(());
+    }
+
+    frame.save_png(&new_path)?;
+    FrameCapture {
+        width: frame.width,
+        height: frame.height,
+        rgba: diff,
+    }
+    .save_png(&diff_path)?;
+    bail!(
+        "Snapshot {} differs in {} pixels (tolerance {}), see {}",
+        name,
+        mismatched,
+        tolerance,
+        diff_path.display()
+    )
+}
+
+impl<T: Page + 'static> Drop for PageHarness<T> {
+    // 与 App 退出时一样销毁页面并取消未完成的命令；在异步测试中 drop 时也不能阻塞等待运行时关闭
+    fn drop(&mut self) {
+        self.pages.teardown(&self.state);
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use wgpu_egui_tokio::{
+    CancellationToken, GpuOptions, Page, Render, WgpuState, testing::PageHarness,
+};
+
+fn options(sample_count: u32) -> GpuOptions {
+    GpuOptions {
+        sample_count,
+        depth_format: Some(wgpu::TextureFormat::Depth32Float),
+        force_fallback_adapter: true,
+        ..Default::default()
+    }
+}
+
+#[derive(Default)]
+struct Errors(Vec<String>);
+
+impl Render for Errors {}
+
+impl Page for Errors {
+    type Message = ();
+
+    fn new(
+        _state: &WgpuState,
+        _sender: tokio::sync::mpsc::Sender<()>,
+        _cancel: CancellationToken,
+    ) -> Self {
+        Errors::default()
+    }
+
+    fn on_error(&mut self, error: anyhow::Error, _state: &WgpuState) {
+        self.0.push(error.to_string());
+    }
+}
+
+// WebGPU 只保证支持 1 和 4，软件 adapter 也不支持 8，应该在创建时返回错误而不是之后产生校验错误
+#[test]
+fn unsupported_sample_count_is_rejected() {
+    let result = pollster::block_on(WgpuState::headless_with_options(64, 64, options(8)));
+    let err = result.err().expect("sample count 8 should be rejected");
+    assert!(err.to_string().contains("Sample count 8"), "{}", err);
+}
+
+#[test]
+fn guaranteed_sample_count_renders_without_errors() -> anyhow::Result<()> {
+    let mut harness = PageHarness::<Errors>::with_options(64, 64, options(4))?;
+    harness.render_frames(2)?;
+    harness.flush_messages();
+    assert_eq!(harness.page().0, Vec::<String>::new());
+    Ok(())
+}
@@ -0,0 +1,197 @@
+use std::{
+    any::Any,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tokio::sync::watch;
+use wgpu_egui_tokio::{
+    CancellationToken, Command, Page, PagePolicy, Params, Render, Subscription, WgpuState,
+    testing::PageHarness,
+};
+
+#[derive(Debug)]
+enum Message {
+    Add(u32),
+    Load,
+    Loaded(u32),
+    Hang,
+}
+
+#[derive(Default)]
+struct Counter {
+    count: u32,
+    file: Option<String>,
+    entered: u32,
+    restored: bool,
+}
+
+impl Render for Counter {}
+
+impl Page for Counter {
+    type Message = Message;
+    const POLICY: PagePolicy = PagePolicy::UnloadOnLeave;
+
+    fn new(
+        _state: &WgpuState,
+        _sender: tokio::sync::mpsc::Sender<Message>,
+        _cancel: CancellationToken,
+    ) -> Self {
+        Counter::default()
+    }
+
+    fn update(&mut self, message: Message, _state: &WgpuState) -> Command<Message> {
+        match message {
+            Message::Add(n) | Message::Loaded(n) => self.count += n,
+            Message::Hang => {
+                return Command::perform(std::future::pending::<u32>(), Message::Loaded);
+            }
+            Message::Load => {
+                return Command::perform(
+                    async {
+                        tokio::time::sleep(Duration::from_millis(20)).await;
+                        5
+                    },
+                    Message::Loaded,
+                );
+            }
+        }
+        Command::none()
+    }
+
+    fn on_enter(&mut self, params: &Params, _state: &WgpuState) {
+        self.entered += 1;
+        self.file = params.raw("file").map(str::to_string);
+    }
+
+    fn snapshot(&self) -> Option<Box<dyn Any + Send>> {
+        Some(Box::new(self.count))
+    }
+
+    fn restore(&mut self, snapshot: Box<dyn Any + Send>, _state: &WgpuState) {
+        if let Ok(count) = snapshot.downcast::<u32>() {
+            self.count = *count;
+            self.restored = true;
+        }
+    }
+}
+
+#[test]
+fn messages_and_commands_reach_the_page() -> anyhow::Result<()> {
+    let mut harness = PageHarness::<Counter>::new(64, 64)?;
+    assert_eq!(harness.page().entered, 1);
+
+    harness.message(Message::Add(2));
+    assert_eq!(harness.page().count, 2);
+
+    harness.message(Message::Load).wait_for_commands()?;
+    assert_eq!(harness.page().count, 7);
+    Ok(())
+}
+
+#[test]
+fn wait_for_commands_times_out() -> anyhow::Result<()> {
+    let mut harness = PageHarness::<Counter>::new(64, 64)?;
+    let result = harness
+        .message(Message::Hang)
+        .wait_for_commands_timeout(Duration::from_millis(50));
+    assert!(result.is_err());
+    Ok(())
+}
+
+#[test]
+fn reload_restores_snapshot() -> anyhow::Result<()> {
+    let mut harness = PageHarness::<Counter>::new(64, 64)?;
+    harness.message(Message::Add(3)).reload();
+    let page = harness.page();
+    assert!(page.restored);
+    assert_eq!(page.count, 3);
+    assert_eq!(page.entered, 1);
+    Ok(())
+}
+
+#[test]
+fn enter_with_params() -> anyhow::Result<()> {
+    let mut harness = PageHarness::<Counter>::new(64, 64)?;
+    let route: wgpu_egui_tokio::Route = "counter?file=foo.gltf".parse()?;
+    harness.leave().enter_with(route.params);
+    assert_eq!(harness.page().file.as_deref(), Some("foo.gltf"));
+    assert_eq!(harness.page().entered, 2);
+    Ok(())
+}
+
+// 由测试创建 watch 通道，页面在 new 中取走接收端
+static WATCHED: Mutex<Option<watch::Receiver<u32>>> = Mutex::new(None);
+
+struct Watching {
+    receiver: watch::Receiver<u32>,
+    values: Vec<u32>,
+}
+
+impl Render for Watching {}
+
+impl Page for Watching {
+    type Message = u32;
+
+    fn new(
+        _state: &WgpuState,
+        _sender: tokio::sync::mpsc::Sender<u32>,
+        _cancel: CancellationToken,
+    ) -> Self {
+        Watching {
+            receiver: WATCHED.lock().unwrap().take().expect("watch receiver"),
+            values: Vec::new(),
+        }
+    }
+
+    fn update(&mut self, value: u32, _state: &WgpuState) -> Command<u32> {
+        self.values.push(value);
+        Command::none()
+    }
+
+    fn subscriptions(&self) -> Subscription<u32> {
+        Subscription::watch("value", self.receiver.clone(), |value| value)
+    }
+}
+
+// 订阅任务在后台发送消息，等待页面收到 n 条
+fn wait_for_values(harness: &mut PageHarness<Watching>, n: usize) -> Vec<u32> {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while harness.page().values.len() < n && Instant::now() < deadline {
+        harness.flush_messages();
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    harness.page().values.clone()
+}
+
+// 给已经停止或不应该重启的订阅留出发送消息的时间，然后返回页面收到的所有消息
+fn settle(harness: &mut PageHarness<Watching>) -> Vec<u32> {
+    for _ in 0..10 {
+        harness.flush_messages();
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    harness.page().values.clone()
+}
+
+#[test]
+fn subscriptions_follow_the_current_page() -> anyhow::Result<()> {
+    let (sender, receiver) = watch::channel(0);
+    *WATCHED.lock().unwrap() = Some(receiver);
+    let mut harness = PageHarness::<Watching>::new(64, 64)?;
+
+    // watch 启动时发送当前值，之后每次变化发送一次
+    assert_eq!(wait_for_values(&mut harness, 1), [0]);
+    sender.send(1)?;
+    assert_eq!(wait_for_values(&mut harness, 2), [0, 1]);
+    // 每帧返回同一个 id，任务不会重启，所以不会再次收到当前值
+    assert_eq!(settle(&mut harness), [0, 1]);
+
+    // 离开页面后订阅停止
+    harness.leave();
+    sender.send(2)?;
+    assert_eq!(settle(&mut harness), [0, 1]);
+
+    // 重新进入后重新启动，先收到当前值
+    harness.enter();
+    assert_eq!(wait_for_values(&mut harness, 3), [0, 1, 2]);
+    Ok(())
+}
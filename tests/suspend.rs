@@ -0,0 +1,96 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use wgpu_egui_tokio::{
+    CancellationToken, FrameContext, Page, Render, WgpuState, testing::PageHarness, wgpu,
+};
+use winit::dpi::PhysicalSize;
+
+static NEXT_ID: AtomicU32 = AtomicU32::new(0);
+
+struct Lifecycle {
+    id: u32,
+    rendered: u32,
+    suspended: u32,
+    resumed: u32,
+    size: Option<PhysicalSize<u32>>,
+}
+
+impl Render for Lifecycle {
+    fn render_frame(&self, frame: &mut FrameContext) -> anyhow::Result<()> {
+        frame
+            .encoder
+            .begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(frame.color_attachment(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLUE),
+                    store: wgpu::StoreOp::Store,
+                }))],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+        Ok(())
+    }
+
+    fn ui_frame(&mut self, _ctx: &egui::Context, _frame: &FrameContext) {
+        self.rendered += 1;
+    }
+}
+
+impl Page for Lifecycle {
+    type Message = ();
+
+    fn new(
+        _state: &WgpuState,
+        _sender: tokio::sync::mpsc::Sender<()>,
+        _cancel: CancellationToken,
+    ) -> Self {
+        Lifecycle {
+            id: NEXT_ID.fetch_add(1, Ordering::SeqCst),
+            rendered: 0,
+            suspended: 0,
+            resumed: 0,
+            size: None,
+        }
+    }
+
+    fn on_resize(&mut self, size: PhysicalSize<u32>, _state: &WgpuState) {
+        self.size = Some(size);
+    }
+
+    fn on_suspend(&mut self, _state: &WgpuState) {
+        self.suspended += 1;
+    }
+
+    fn on_resume(&mut self, _state: &WgpuState) {
+        self.resumed += 1;
+    }
+}
+
+#[test]
+fn suspend_keeps_page_and_skips_frames() -> anyhow::Result<()> {
+    let mut harness = PageHarness::<Lifecycle>::new(64, 48)?;
+    harness.render_frames(2)?;
+    let id = harness.page().id;
+    assert_eq!(harness.page().rendered, 2);
+
+    harness.suspend();
+    assert!(harness.state.is_suspended());
+    assert_eq!(harness.page().suspended, 1);
+    harness.render_frames(3)?;
+    assert_eq!(harness.page().rendered, 2);
+
+    // 挂起期间窗口尺寸变化，恢复后应按新的尺寸渲染
+    harness.resize(80, 60).resume()?;
+    assert!(!harness.state.is_suspended());
+    assert_eq!(harness.page().resumed, 1);
+    assert_eq!(harness.page().size, Some(PhysicalSize::new(80, 60)));
+
+    let frame = harness.render_frames(1)?;
+    assert_eq!((frame.width, frame.height), (80, 60));
+    assert_eq!(&frame.rgba[..4], &[0, 0, 255, 255]);
+
+    let page = harness.page();
+    assert_eq!(page.id, id);
+    assert_eq!(page.rendered, 3);
+    Ok(())
+}